@@ -0,0 +1,249 @@
+use crate::slash_path;
+use licenselint::report::CheckReport;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// The baseline file used when `--baseline` or `--write-baseline` is given without one, in the
+/// directory of `licenselint.toml`.
+pub const DEFAULT_BASELINE: &str = ".licenselint-baseline.json";
+
+/// Issues recorded by `check --write-baseline`, which `check --baseline` does not fail on as long
+/// as the file they are in is unchanged.
+#[derive(Serialize, Deserialize)]
+pub struct Baseline {
+    version: u32,
+    entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Entry {
+    /// The file, relative to the directory of `licenselint.toml`, or the current directory
+    /// without one, with `/` separators.
+    pub path: String,
+    pub kind: String,
+    /// The SHA-256 of the file content, so editing the file brings its issues back.
+    pub hash: String,
+}
+
+impl Baseline {
+    /// Records every issue of `report`, whose files are relative to `root`.
+    pub fn from_report(report: &CheckReport, root: &Path) -> io::Result<Self> {
+        let mut hashes: HashMap<&Path, String> = HashMap::new();
+        let mut entries = BTreeSet::new();
+
+        for issue in &report.issues {
+            let hash = match hashes.get(issue.filename.as_path()) {
+                Some(hash) => hash.clone(),
+                None => {
+                    let hash = file_hash(&issue.filename)?;
+                    hashes.insert(issue.filename.as_path(), hash.clone());
+                    hash
+                }
+            };
+
+            entries.insert(Entry {
+                path: slash_path::relative_to_slash(root, &issue.filename),
+                kind: issue.kind.as_str().to_string(),
+                hash,
+            });
+        }
+
+        Ok(Baseline {
+            version: 1,
+            entries: entries.into_iter().collect(),
+        })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let baseline: Baseline = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        if baseline.version != 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported baseline version {}", baseline.version),
+            ));
+        }
+
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut content = serde_json::to_string_pretty(self).expect("baseline is serializable");
+        content.push('\n');
+        fs::write(path, content)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Removes the issues recorded in the baseline from `report`, and returns the entries of files
+    /// under `paths` that no longer have the issue.
+    pub fn apply(&self, report: &mut CheckReport, root: &Path, paths: &[PathBuf]) -> Vec<&Entry> {
+        let entries: HashSet<&Entry> = self.entries.iter().collect();
+        let mut hashes: HashMap<PathBuf, Option<String>> = HashMap::new();
+        let mut found = HashSet::new();
+
+        report.issues.retain(|issue| {
+            let path = slash_path::relative_to_slash(root, &issue.filename);
+            let kind = issue.kind.as_str();
+            found.insert((path.clone(), kind));
+
+            let hash = hashes
+                .entry(issue.filename.clone())
+                .or_insert_with(|| file_hash(&issue.filename).ok());
+
+            match hash {
+                Some(hash) => !entries.contains(&Entry {
+                    path,
+                    kind: kind.to_string(),
+                    hash: hash.clone(),
+                }),
+                None => true,
+            }
+        });
+
+        self.entries
+            .iter()
+            .filter(|entry| !found.contains(&(entry.path.clone(), entry.kind.as_str())))
+            .filter(|entry| entry.is_under(root, paths))
+            .collect()
+    }
+
+    /// Adds the entries of `previous` for files outside `paths`, which were not checked again.
+    pub fn keep_outside(&mut self, previous: Baseline, root: &Path, paths: &[PathBuf]) {
+        self.entries.extend(
+            previous
+                .entries
+                .into_iter()
+                .filter(|entry| !entry.is_under(root, paths)),
+        );
+        self.entries.sort();
+        self.entries.dedup();
+    }
+}
+
+impl Entry {
+    /// Whether the file of the entry is one of `paths` or in one of them, relative to `root`.
+    fn is_under(&self, root: &Path, paths: &[PathBuf]) -> bool {
+        let file = root.join(&self.path);
+        paths.iter().any(|path| file.starts_with(root.join(path)))
+    }
+}
+
+fn file_hash(path: &Path) -> io::Result<String> {
+    Ok(format!("{:x}", Sha256::digest(fs::read(path)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use licenselint::issue::{Issue, IssueKind, Span};
+
+    fn report(files: &[&Path]) -> CheckReport {
+        CheckReport {
+            issues: files
+                .iter()
+                .map(|file| {
+                    Issue::new(
+                        file,
+                        IssueKind::MissingHeader,
+                        Span::line(1, "fn main() {}"),
+                        "missing Apache-2.0 license header".to_string(),
+                    )
+                })
+                .collect(),
+            ..CheckReport::default()
+        }
+    }
+
+    #[test]
+    fn test_from_report() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let file = dir.join("src/main.rs");
+        fs::write(&file, "fn main() {}\n").unwrap();
+
+        let baseline = Baseline::from_report(&report(&[&file, &file]), dir).unwrap();
+        assert_eq!(
+            baseline.len(),
+            1,
+            "Issues of one kind in a file share an entry"
+        );
+        assert_eq!(baseline.entries[0].path, "src/main.rs");
+        assert_eq!(baseline.entries[0].kind, "missing-header");
+        assert_eq!(baseline.entries[0].hash, file_hash(&file).unwrap());
+
+        let path = dir.join(DEFAULT_BASELINE);
+        baseline.save(&path).unwrap();
+        assert_eq!(Baseline::load(&path).unwrap().entries, baseline.entries);
+    }
+
+    #[test]
+    fn test_apply() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let (a, b, c) = (dir.join("src/a.rs"), dir.join("src/b.rs"), dir.join("c.rs"));
+        for file in [&a, &b, &c] {
+            fs::write(file, "fn main() {}\n").unwrap();
+        }
+        let baseline = Baseline::from_report(&report(&[&a, &b, &c]), dir).unwrap();
+
+        let mut unchanged = report(&[&a, &b, &c]);
+        assert!(baseline
+            .apply(&mut unchanged, dir, &[dir.to_path_buf()])
+            .is_empty());
+        assert!(
+            unchanged.issues.is_empty(),
+            "Recorded issues in unchanged files should be dropped"
+        );
+
+        fs::write(&a, "fn main() { edited() }\n").unwrap();
+        let new_file = dir.join("src/d.rs");
+        fs::write(&new_file, "fn main() {}\n").unwrap();
+
+        let mut report_after = report(&[&a, &new_file]);
+        let fixed = baseline.apply(&mut report_after, dir, &[dir.to_path_buf()]);
+        let remaining: Vec<&Path> = report_after
+            .issues
+            .iter()
+            .map(|issue| issue.filename.as_path())
+            .collect();
+        assert_eq!(
+            remaining,
+            vec![a.as_path(), new_file.as_path()],
+            "Issues in edited or new files should be reported"
+        );
+        let fixed: Vec<&str> = fixed.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(fixed, vec!["c.rs", "src/b.rs"]);
+
+        let mut report_in_src = report(&[&a]);
+        let fixed = baseline.apply(&mut report_in_src, dir, &[dir.join("src")]);
+        let fixed: Vec<&str> = fixed.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(
+            fixed,
+            vec!["src/b.rs"],
+            "Entries of files outside the checked paths should not be reported as fixed"
+        );
+
+        let mut in_src = Baseline::from_report(&report(&[&a]), dir).unwrap();
+        in_src.keep_outside(baseline, dir, &[dir.join("src")]);
+        let paths: Vec<&str> = in_src
+            .entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["c.rs", "src/a.rs"],
+            "Rewriting the baseline for some paths should keep the entries of the others"
+        );
+    }
+}
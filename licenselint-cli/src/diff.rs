@@ -0,0 +1,25 @@
+use crate::slash_path;
+use licenselint::report::FormatChange;
+use similar::TextDiff;
+
+/// Renders the changes as a git-style unified diff that `git apply` accepts from the formatted
+/// directory.
+pub fn render(changes: &[FormatChange]) -> String {
+    let mut patch = String::new();
+
+    for change in changes {
+        let path = slash_path::to_slash(&change.relative_path);
+        let old_path = format!("a/{}", path);
+        let new_path = format!("b/{}", path);
+
+        patch.push_str(&format!("diff --git {} {}\n", old_path, new_path));
+        patch.push_str(
+            &TextDiff::from_lines(&change.original, &change.formatted)
+                .unified_diff()
+                .header(&old_path, &new_path)
+                .to_string(),
+        );
+    }
+
+    patch
+}
@@ -0,0 +1,166 @@
+use crate::slash_path;
+use licenselint::issue::Issue;
+use licenselint::report::CheckReport;
+use licenselint::Error;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The JSON document printed by `check --format json`. Bump `version` on incompatible changes.
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    version: u32,
+    summary: JsonSummary<'a>,
+    issues: Vec<JsonIssue<'a>>,
+    errors: Vec<JsonError>,
+}
+
+#[derive(Serialize)]
+struct JsonSummary<'a> {
+    files_scanned: usize,
+    files_skipped: usize,
+    issues: usize,
+    errors: usize,
+    languages: &'a BTreeMap<String, usize>,
+}
+
+#[derive(Serialize)]
+struct JsonIssue<'a> {
+    file: String,
+    kind: &'static str,
+    severity: &'static str,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    message: &'a str,
+    expected: Option<&'a str>,
+    found: Option<&'a str>,
+}
+
+impl<'a> JsonIssue<'a> {
+    fn new(issue: &'a Issue, root: &Path) -> Self {
+        JsonIssue {
+            file: slash_path::relative_to_slash(root, &issue.filename),
+            kind: issue.kind.as_str(),
+            severity: issue.severity.as_str(),
+            line: issue.span.start_line,
+            column: issue.span.start_column,
+            end_line: issue.span.end_line,
+            end_column: issue.span.end_column,
+            message: &issue.message,
+            expected: issue.expected.as_deref(),
+            found: issue.found.as_deref(),
+        }
+    }
+}
+
+/// A file that could not be checked.
+#[derive(Serialize)]
+struct JsonError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    message: String,
+}
+
+impl JsonError {
+    fn new(error: &Error, root: &Path) -> Self {
+        JsonError {
+            file: error
+                .path()
+                .map(|path| slash_path::relative_to_slash(root, path)),
+            message: error.message(),
+        }
+    }
+}
+
+/// Renders `report` with file paths relative to `root`.
+pub fn render(report: &CheckReport, root: &Path) -> String {
+    let json_report = JsonReport {
+        version: 1,
+        summary: JsonSummary {
+            files_scanned: report.files_scanned,
+            files_skipped: report.files_skipped,
+            issues: report.issues.len(),
+            errors: report.errors.len(),
+            languages: &report.languages,
+        },
+        issues: report
+            .issues
+            .iter()
+            .map(|issue| JsonIssue::new(issue, root))
+            .collect(),
+        errors: report
+            .errors
+            .iter()
+            .map(|error| JsonError::new(error, root))
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&json_report).expect("JSON report serialization failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use licenselint::issue::{IssueKind, Span};
+    use serde_json::json;
+    use std::io;
+
+    #[test]
+    fn test_render() {
+        let root = Path::new("/repo");
+        let mut issue = Issue::new(
+            &root.join("src/main.rs"),
+            IssueKind::WrongAuthor,
+            Span::line(1, "// Copyright 2024 Someone"),
+            "copyright holder is not allowed: expected 'Your Name', found 'Someone'".to_string(),
+        );
+        issue.expected = Some("Your Name".to_string());
+        issue.found = Some("Someone".to_string());
+
+        let mut report = CheckReport {
+            files_scanned: 2,
+            files_skipped: 1,
+            issues: vec![issue],
+            errors: vec![Error::io(
+                &root.join("src/lib.rs"),
+                io::Error::new(io::ErrorKind::PermissionDenied, "permission denied"),
+            )],
+            ..CheckReport::default()
+        };
+        report.languages.insert("rust".to_string(), 2);
+
+        let rendered: serde_json::Value = serde_json::from_str(&render(&report, root)).unwrap();
+        assert_eq!(
+            rendered,
+            json!({
+                "version": 1,
+                "summary": {
+                    "files_scanned": 2,
+                    "files_skipped": 1,
+                    "issues": 1,
+                    "errors": 1,
+                    "languages": { "rust": 2 }
+                },
+                "issues": [
+                    {
+                        "file": "src/main.rs",
+                        "kind": "wrong-author",
+                        "severity": "error",
+                        "line": 1,
+                        "column": 1,
+                        "end_line": 1,
+                        "end_column": 26,
+                        "message": "copyright holder is not allowed: expected 'Your Name', found 'Someone'",
+                        "expected": "Your Name",
+                        "found": "Someone"
+                    }
+                ],
+                "errors": [
+                    { "file": "src/lib.rs", "message": "permission denied" }
+                ]
+            })
+        );
+    }
+}
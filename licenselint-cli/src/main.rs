@@ -1,45 +1,390 @@
-use chrono::{Datelike, Local};
-use clap::{Arg, Command};
-use licenselint::config::Config;
-use licenselint::license::License;
+mod baseline;
+mod diff;
+mod json;
+mod sarif;
+mod slash_path;
+
+use baseline::Baseline;
+use clap::{Arg, ArgAction, Command};
+use licenselint::config::FileConfig;
+use licenselint::git;
 use licenselint::linter::Linter;
-use std::path::Path;
+use licenselint::report::CheckReport;
+use licenselint::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
 
-fn check(current_dir: &Path, linter: &Linter) {
-    match linter.check_files_in_dir(current_dir) {
-        Ok(issues) => {
-            if issues.is_empty() {
-                println!("No issues found.");
-            } else {
-                for issue in issues {
-                    println!("Issue found in '{}'", issue.filename,);
-                }
-                std::process::exit(2);
+/// Exit code when issues are found or files would be formatted.
+const EXIT_ISSUES: i32 = 2;
+/// Exit code when some files could not be read, linted or written.
+const EXIT_FILE_ERRORS: i32 = 3;
+
+fn check(
+    root: &Path,
+    paths: &[PathBuf],
+    linter: &Linter,
+    output_format: &str,
+    baseline_path: Option<&Path>,
+) {
+    let mut report = linter.check_paths(root, paths);
+
+    if let Some(baseline_path) = baseline_path {
+        let baseline = match Baseline::load(baseline_path) {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                eprintln!(
+                    "Error loading baseline '{}': {}",
+                    baseline_path.display(),
+                    e
+                );
+                std::process::exit(1);
             }
+        };
+
+        let fixed = baseline.apply(&mut report, root, paths);
+        for entry in &fixed {
+            eprintln!("Fixed baseline entry {} [{}]", entry.path, entry.kind);
+        }
+        if !fixed.is_empty() {
+            eprintln!(
+                "{} baselined issue(s) fixed, rerun with --write-baseline to drop them.",
+                fixed.len()
+            );
         }
-        Err(errors) => {
-            for e in errors {
-                eprintln!("Error checking files: {:?}", e);
+    }
+
+    print_report(&report, root, linter, output_format, &|filename| {
+        fs::read_to_string(filename).ok()
+    });
+}
+
+/// Records the current issues as the baseline at `baseline_path`, which `check --baseline` then
+/// does not fail on. Entries of an existing baseline for files outside `paths` are kept.
+fn write_baseline(root: &Path, paths: &[PathBuf], linter: &Linter, baseline_path: &Path) {
+    let report = linter.check_paths(root, paths);
+
+    let previous = match Baseline::load(baseline_path) {
+        Ok(previous) => Some(previous),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => {
+            eprintln!(
+                "Error loading baseline '{}': {}",
+                baseline_path.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let result = Baseline::from_report(&report, root).and_then(|mut baseline| {
+        if let Some(previous) = previous {
+            baseline.keep_outside(previous, root, paths);
+        }
+        baseline.save(baseline_path).map(|_| baseline)
+    });
+    match result {
+        Ok(baseline) => println!(
+            "Recorded {} issue(s) in {}.",
+            baseline.len(),
+            baseline_path.display()
+        ),
+        Err(e) => {
+            eprintln!(
+                "Error writing baseline '{}': {}",
+                baseline_path.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    }
+
+    exit_on_errors("checking", &report.errors);
+}
+
+/// Checks the content of stdin as if it were the file `filename`, relative to `root`.
+fn check_stdin(root: &Path, filename: &Path, linter: &Linter, output_format: &str) {
+    let content = read_stdin();
+    let mut report = CheckReport::default();
+
+    match linter.template_for(filename) {
+        Some(template) => {
+            report.files_scanned = 1;
+            report.languages.insert(template.language().to_string(), 1);
+            report.issues = linter.check_content(root, filename, &content);
+        }
+        None => report.files_skipped = 1,
+    }
+
+    print_report(&report, root, linter, output_format, &|_| {
+        Some(content.clone())
+    });
+}
+
+fn print_report(
+    report: &CheckReport,
+    root: &Path,
+    linter: &Linter,
+    output_format: &str,
+    read_file: &dyn Fn(&Path) -> Option<String>,
+) {
+    if output_format == "json" {
+        println!("{}", json::render(report, root));
+    } else if output_format == "sarif" {
+        println!("{}", sarif::render(report, root, linter, read_file));
+    } else if report.issues.is_empty() && report.errors.is_empty() {
+        println!("No issues found.");
+    } else {
+        for issue in &report.issues {
+            println!("{}", issue);
+        }
+    }
+
+    exit_on_errors("checking", &report.errors);
+
+    if !report.issues.is_empty() {
+        std::process::exit(EXIT_ISSUES);
+    }
+}
+
+/// Prints the files that could not be processed and exits with `EXIT_FILE_ERRORS` if any.
+fn exit_on_errors(action: &str, errors: &[Error]) {
+    for e in errors {
+        eprintln!("Error {} {}", action, e);
+    }
+
+    if !errors.is_empty() {
+        std::process::exit(EXIT_FILE_ERRORS);
+    }
+}
+
+fn format(root: &Path, paths: &[PathBuf], linter: &Linter) {
+    let report = linter.format_paths(root, paths);
+
+    if report.errors.is_empty() {
+        println!("Files formatted successfully.");
+    } else {
+        println!("Formatted {} file(s).", report.formatted.len());
+    }
+
+    exit_on_errors("formatting", &report.errors);
+}
+
+/// Prints the content of stdin formatted as if it were the file `filename`, relative to `root`.
+fn format_stdin(root: &Path, filename: &Path, linter: &Linter) {
+    print!("{}", linter.format_content(root, filename, &read_stdin()));
+}
+
+fn read_stdin() -> String {
+    let mut content = String::new();
+
+    if let Err(e) = io::stdin().read_to_string(&mut content) {
+        eprintln!("Error reading stdin: {}", e);
+        std::process::exit(1);
+    }
+
+    content
+}
+
+/// Lists the files `format` would change without writing them, exiting with 2 if there are any.
+fn format_check(root: &Path, paths: &[PathBuf], linter: &Linter) {
+    let report = linter.format_changes_in_paths(root, paths);
+
+    if report.changes.is_empty() && report.errors.is_empty() {
+        println!("All files are formatted.");
+    }
+
+    for change in &report.changes {
+        println!("Would format {}", change.relative_path.display());
+    }
+
+    if !report.changes.is_empty() {
+        println!("{} file(s) would be formatted.", report.changes.len());
+    }
+
+    exit_on_errors("formatting", &report.errors);
+
+    if !report.changes.is_empty() {
+        std::process::exit(EXIT_ISSUES);
+    }
+}
+
+/// Prints the changes `format` would make, or writes them to `patch_path`, without touching the
+/// formatted files.
+fn format_diff(root: &Path, paths: &[PathBuf], linter: &Linter, patch_path: Option<&Path>) {
+    let report = linter.format_changes_in_paths(root, paths);
+    let patch = diff::render(&report.changes);
+
+    match patch_path {
+        Some(patch_path) => {
+            if let Err(e) = std::fs::write(patch_path, patch) {
+                eprintln!("Error writing patch '{}': {}", patch_path.display(), e);
+                std::process::exit(1);
             }
+            println!(
+                "Wrote changes to {} file(s) to {}.",
+                report.changes.len(),
+                patch_path.display()
+            );
         }
+        None => print!("{}", patch),
     }
+
+    exit_on_errors("formatting", &report.errors);
+}
+
+fn paths_arg() -> Arg {
+    Arg::new("paths")
+        .value_parser(clap::builder::ValueParser::path_buf())
+        .action(ArgAction::Append)
+        .help("Files and directories to lint, the current directory by default")
+}
+
+fn respect_ignore_arg() -> Arg {
+    Arg::new("respect-ignore")
+        .long("respect-ignore")
+        .action(ArgAction::SetTrue)
+        .help("Skip files passed as arguments when they are ignored or excluded")
+}
+
+fn stdin_arg() -> Arg {
+    Arg::new("stdin")
+        .long("stdin")
+        .action(ArgAction::SetTrue)
+        .requires("stdin-filename")
+        .conflicts_with_all(["paths", "since", "staged"])
+        .help("Read the content to lint from stdin")
+}
+
+fn stdin_filename_arg() -> Arg {
+    Arg::new("stdin-filename")
+        .long("stdin-filename")
+        .value_parser(clap::builder::ValueParser::path_buf())
+        .requires("stdin")
+        .help("The path of the file read from stdin, which picks its template and overrides")
+}
+
+fn baseline_arg() -> Arg {
+    Arg::new("baseline")
+        .long("baseline")
+        .value_parser(clap::builder::ValueParser::path_buf())
+        .num_args(0..=1)
+        .conflicts_with_all(["write-baseline", "stdin"])
+        .help("Only fail on issues missing from this baseline file, .licenselint-baseline.json next to licenselint.toml by default")
+}
+
+fn write_baseline_arg() -> Arg {
+    Arg::new("write-baseline")
+        .long("write-baseline")
+        .value_parser(clap::builder::ValueParser::path_buf())
+        .num_args(0..=1)
+        .conflicts_with_all(["format", "stdin"])
+        .help("Record the current issues in this baseline file, .licenselint-baseline.json next to licenselint.toml by default")
 }
 
-fn format(current_dir: &Path, linter: &Linter) {
-    match linter.format_files_in_dir(current_dir) {
-        Ok(_) => println!("Files formatted successfully."),
-        Err(errors) => {
-            for e in errors {
-                eprintln!("Error formatting files: {:?}", e);
+fn since_arg() -> Arg {
+    Arg::new("since")
+        .long("since")
+        .value_parser(clap::builder::ValueParser::string())
+        .conflicts_with("staged")
+        .help("Only lint files changed since a git ref, including uncommitted and untracked files")
+}
+
+fn staged_arg() -> Arg {
+    Arg::new("staged")
+        .long("staged")
+        .action(ArgAction::SetTrue)
+        .help("Only lint files with staged changes")
+}
+
+/// The absolute paths given to a subcommand, or the current directory. With `--since` or
+/// `--staged`, the files changed in git under those paths, which are then skipped when ignored.
+fn paths(matches: &clap::ArgMatches, current_dir: &Path, linter: &mut Linter) -> Vec<PathBuf> {
+    let paths: Vec<PathBuf> = match matches.get_many::<PathBuf>("paths") {
+        Some(paths) => paths.map(|path| absolute(current_dir, path)).collect(),
+        None => vec![current_dir.to_path_buf()],
+    };
+
+    linter.set_respect_ignore(matches.get_flag("respect-ignore"));
+
+    let changed_files = if let Some(reference) = matches.get_one::<String>("since") {
+        git::changed_since(current_dir, reference)
+    } else if matches.get_flag("staged") {
+        git::staged(current_dir)
+    } else {
+        return paths;
+    };
+
+    let changed_files = match changed_files {
+        Ok(changed_files) => changed_files,
+        Err(e) => {
+            eprintln!("Error listing changed files: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    linter.set_respect_ignore(true);
+
+    changed_files
+        .into_iter()
+        .map(|file| current_dir.join(file))
+        .filter(|file| paths.iter().any(|path| file.starts_with(path)))
+        .collect()
+}
+
+/// `path` joined to `current_dir` with its `.` and `..` segments resolved, so it starts with the
+/// root and matches the config globs like the paths the walk finds.
+fn absolute(current_dir: &Path, path: &Path) -> PathBuf {
+    let mut absolute = PathBuf::new();
+
+    for component in current_dir.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
             }
+            component => absolute.push(component),
         }
     }
+
+    absolute
+}
+
+/// The baseline file given to the `id` argument, relative to the current directory, or
+/// `DEFAULT_BASELINE` in `root` when the argument has no value, since baseline entries are
+/// relative to `root`.
+fn baseline_path(
+    matches: &clap::ArgMatches,
+    id: &str,
+    root: &Path,
+    current_dir: &Path,
+) -> Option<PathBuf> {
+    if !matches.contains_id(id) {
+        return None;
+    }
+
+    Some(match matches.get_one::<PathBuf>(id) {
+        Some(path) => absolute(current_dir, path),
+        None => root.join(baseline::DEFAULT_BASELINE),
+    })
+}
+
+/// `path` relative to `root`, which is how the linter matches it against the config globs.
+fn relative_to(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
 }
 
 fn main() {
     let matches = Command::new("licenselint-cli")
         .version("0.0.5")
         .about("A command-line tool for linting and fixing license formatting issues")
+        .arg(
+            Arg::new("config")
+                .short('c')
+                .long("config")
+                .value_parser(clap::builder::ValueParser::string())
+                .help("The config file to use instead of the discovered licenselint.toml"),
+        )
         .arg(
             Arg::new("author")
                 .short('a')
@@ -54,42 +399,257 @@ fn main() {
                 .value_parser(clap::builder::ValueParser::string())
                 .help("The author email to include in the license"),
         )
-        .subcommand(Command::new("check").about("Check files for lint issues"))
-        .subcommand(Command::new("format").about("Automatically format files to fix lint issues"))
+        .arg(
+            Arg::new("allowed-author")
+                .long("allowed-author")
+                .action(ArgAction::Append)
+                .value_parser(clap::builder::ValueParser::string())
+                .help("An additional copyright holder accepted by check, can be repeated"),
+        )
+        .arg(
+            Arg::new("license")
+                .short('l')
+                .long("license")
+                .value_parser(clap::builder::ValueParser::string())
+                .help("The license to check and format with (Apache-2.0, MIT)"),
+        )
+        .arg(
+            Arg::new("header-style")
+                .long("header-style")
+                .value_parser(clap::builder::ValueParser::string())
+                .help("The license header style, full boilerplate or SPDX short form (full, spdx)"),
+        )
+        .arg(
+            Arg::new("existing-header")
+                .long("existing-header")
+                .value_parser(clap::builder::ValueParser::string())
+                .help("What format does with an outdated license header (update, replace)"),
+        )
+        .arg(
+            Arg::new("include")
+                .long("include")
+                .value_parser(clap::builder::ValueParser::string())
+                .action(ArgAction::Append)
+                .help("Only lint files matching this glob, relative to the directory of licenselint.toml (can be repeated)"),
+        )
+        .arg(
+            Arg::new("exclude")
+                .long("exclude")
+                .value_parser(clap::builder::ValueParser::string())
+                .action(ArgAction::Append)
+                .help("Never lint files matching this glob, relative to the directory of licenselint.toml (can be repeated)"),
+        )
+        .arg(
+            Arg::new("no-default-exclude")
+                .long("no-default-exclude")
+                .action(ArgAction::SetTrue)
+                .help("Also lint build output, dependencies and lockfiles like target/ and node_modules/"),
+        )
+        .arg(
+            Arg::new("max-file-size")
+                .long("max-file-size")
+                .value_parser(clap::value_parser!(u64))
+                .help("Skip files larger than this many bytes, 10 MiB by default"),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Check files for lint issues")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["text", "json", "sarif"])
+                        .default_value("text")
+                        .help("The output format of the issues"),
+                )
+                .arg(paths_arg())
+                .arg(respect_ignore_arg())
+                .arg(since_arg())
+                .arg(staged_arg())
+                .arg(baseline_arg())
+                .arg(write_baseline_arg())
+                .arg(stdin_arg())
+                .arg(stdin_filename_arg()),
+        )
+        .subcommand(
+            Command::new("format")
+                .about("Automatically format files to fix lint issues")
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["diff", "patch", "stdin"])
+                        .help("List the files that would be formatted and exit with 2 if any, without writing files"),
+                )
+                .arg(
+                    Arg::new("diff")
+                        .long("diff")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["patch", "stdin"])
+                        .help("Print a unified diff of the changes instead of writing files"),
+                )
+                .arg(
+                    Arg::new("patch")
+                        .long("patch")
+                        .value_parser(clap::builder::ValueParser::string())
+                        .conflicts_with("stdin")
+                        .help("Write the changes to a patch file for git apply instead of writing files"),
+                )
+                .arg(paths_arg())
+                .arg(respect_ignore_arg())
+                .arg(since_arg())
+                .arg(staged_arg())
+                .arg(stdin_arg())
+                .arg(stdin_filename_arg()),
+        )
         .get_matches();
 
     let current_dir = std::env::current_dir().expect("Failed to get current directory");
 
-    let formatted_author = matches
-        .get_one::<String>("author")
-        .map(|s| s.as_str())
-        .unwrap_or("Unknown Author")
-        .to_string();
+    let config_path = match matches.get_one::<String>("config") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => FileConfig::discover(&current_dir),
+    };
 
-    let formatted_email = matches.get_one::<String>("email").map(|s| s.as_str());
+    // Globs are relative to the directory of the config file, so linting from a subdirectory
+    // applies the same overrides, includes and excludes as linting from the project root.
+    let root = config_path
+        .as_deref()
+        .and_then(Path::parent)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map_or_else(|| current_dir.clone(), |dir| absolute(&current_dir, dir));
 
-    let formatted_author = if let Some(email) = formatted_email {
-        format!("{} <{}>", formatted_author, email)
-    } else {
-        formatted_author
+    let mut file_config = match config_path {
+        Some(path) => match FileConfig::load(&path) {
+            Ok(file_config) => file_config,
+            Err(e) => {
+                eprintln!("Error loading config {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => FileConfig::default(),
     };
 
-    let current_year = Local::now().year().to_string();
+    if let Some(author) = matches.get_one::<String>("author") {
+        file_config.author = Some(author.to_string());
+        // The email of the file belongs to its author, not to the one given here.
+        file_config.email = None;
+    }
+
+    if let Some(email) = matches.get_one::<String>("email") {
+        file_config.email = Some(email.to_string());
+    }
+
+    if let Some(license) = matches.get_one::<String>("license") {
+        file_config.license = Some(license.to_string());
+    }
+
+    if let Some(header_style) = matches.get_one::<String>("header-style") {
+        file_config.header_style = Some(header_style.to_string());
+    }
+
+    if let Some(existing_header) = matches.get_one::<String>("existing-header") {
+        file_config.existing_header = Some(existing_header.to_string());
+    }
+
+    if let Some(include) = matches.get_many::<String>("include") {
+        file_config
+            .include
+            .extend(include.map(|glob| glob.to_string()));
+    }
+
+    if let Some(exclude) = matches.get_many::<String>("exclude") {
+        file_config
+            .exclude
+            .extend(exclude.map(|glob| glob.to_string()));
+    }
+
+    if matches.get_flag("no-default-exclude") {
+        file_config.default_exclude = Some(false);
+    }
+
+    if let Some(max_file_size) = matches.get_one::<u64>("max-file-size") {
+        file_config.max_file_size = Some(*max_file_size);
+    }
 
-    let config = Config::new_from_author(
-        License::Apache20,
-        formatted_author.to_string(),
-        current_year,
-    );
+    if let Some(allowed_authors) = matches.get_many::<String>("allowed-author") {
+        file_config
+            .allowed_authors
+            .extend(allowed_authors.map(|author| author.to_string()));
+    }
 
-    let linter = Linter::new(&config);
+    let config = match file_config.into_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut linter = match Linter::new(&config) {
+        Ok(linter) => linter,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     if matches.subcommand().is_none() {
         println!("No subcommand provided, defaulting to 'check'...");
-        check(&current_dir, &linter);
-    } else if matches.subcommand_matches("check").is_some() {
-        check(&current_dir, &linter);
-    } else if matches.subcommand_matches("format").is_some() {
-        format(&current_dir, &linter);
+        check(
+            &root,
+            std::slice::from_ref(&current_dir),
+            &linter,
+            "text",
+            None,
+        );
+    } else if let Some(check_matches) = matches.subcommand_matches("check") {
+        let output_format = check_matches.get_one::<String>("format").unwrap();
+
+        if let Some(filename) = check_matches.get_one::<PathBuf>("stdin-filename") {
+            check_stdin(
+                &root,
+                &relative_to(&root, &absolute(&current_dir, filename)),
+                &linter,
+                output_format,
+            );
+        } else {
+            let paths = paths(check_matches, &current_dir, &mut linter);
+
+            if let Some(baseline_path) =
+                baseline_path(check_matches, "write-baseline", &root, &current_dir)
+            {
+                write_baseline(&root, &paths, &linter, &baseline_path);
+            } else {
+                let baseline_path = baseline_path(check_matches, "baseline", &root, &current_dir);
+                check(
+                    &root,
+                    &paths,
+                    &linter,
+                    output_format,
+                    baseline_path.as_deref(),
+                );
+            }
+        }
+    } else if let Some(format_matches) = matches.subcommand_matches("format") {
+        if let Some(filename) = format_matches.get_one::<PathBuf>("stdin-filename") {
+            format_stdin(
+                &root,
+                &relative_to(&root, &absolute(&current_dir, filename)),
+                &linter,
+            );
+            return;
+        }
+
+        let paths = paths(format_matches, &current_dir, &mut linter);
+
+        if format_matches.get_flag("check") {
+            format_check(&root, &paths, &linter);
+        } else if let Some(patch_path) = format_matches.get_one::<String>("patch") {
+            format_diff(&root, &paths, &linter, Some(Path::new(patch_path)));
+        } else if format_matches.get_flag("diff") {
+            format_diff(&root, &paths, &linter, None);
+        } else {
+            format(&root, &paths, &linter);
+        }
     }
 }
@@ -0,0 +1,324 @@
+use crate::slash_path;
+use licenselint::issue::{Issue, IssueKind, Severity};
+use licenselint::linter::Linter;
+use licenselint::report::CheckReport;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Renders the report as a SARIF 2.1.0 log. Every result carries a fix replacing the lines that
+/// `Linter::format` would change in the content returned by `read_file`, and file URIs are
+/// relative to `root`.
+pub fn render(
+    report: &CheckReport,
+    root: &Path,
+    linter: &Linter,
+    read_file: &dyn Fn(&Path) -> Option<String>,
+) -> String {
+    let rules: Vec<Value> = IssueKind::ALL
+        .iter()
+        .map(|kind| {
+            json!({
+                "id": kind.as_str(),
+                "shortDescription": { "text": kind.description() },
+                "defaultConfiguration": { "level": level(kind.severity()) },
+            })
+        })
+        .collect();
+
+    let mut fixes: HashMap<&Path, Option<Value>> = HashMap::new();
+
+    let results: Vec<Value> = report
+        .issues
+        .iter()
+        .map(|issue| {
+            let uri = relative_uri(root, &issue.filename);
+            let fix = fixes.entry(&issue.filename).or_insert_with(|| {
+                let content = read_file(&issue.filename)?;
+                let relative_path = issue.filename.strip_prefix(root).unwrap_or(&issue.filename);
+                fix(linter, relative_path, content, &uri)
+            });
+            result(issue, &uri, fix.as_ref())
+        })
+        .collect();
+
+    let notifications: Vec<Value> = report
+        .errors
+        .iter()
+        .map(|error| {
+            let mut notification = json!({
+                "level": "error",
+                "message": { "text": error.message() },
+            });
+            if let Some(path) = error.path() {
+                notification["locations"] = json!([{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": relative_uri(root, path),
+                            "uriBaseId": "%SRCROOT%",
+                        }
+                    }
+                }]);
+            }
+            notification
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "licenselint",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "originalUriBaseIds": {
+                "%SRCROOT%": { "uri": directory_uri(root) }
+            },
+            "invocations": [{
+                "executionSuccessful": report.errors.is_empty(),
+                "toolExecutionNotifications": notifications,
+            }],
+            "results": results,
+        }]
+    });
+
+    serde_json::to_string_pretty(&log).expect("SARIF serialization failed")
+}
+
+fn result(issue: &Issue, uri: &str, fix: Option<&Value>) -> Value {
+    let rule_index = IssueKind::ALL
+        .iter()
+        .position(|kind| *kind == issue.kind)
+        .unwrap_or_default();
+
+    let mut result = json!({
+        "ruleId": issue.kind.as_str(),
+        "ruleIndex": rule_index,
+        "level": level(issue.severity),
+        "message": { "text": issue.message },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri, "uriBaseId": "%SRCROOT%" },
+                "region": {
+                    "startLine": issue.span.start_line,
+                    "startColumn": issue.span.start_column,
+                    "endLine": issue.span.end_line,
+                    "endColumn": issue.span.end_column,
+                }
+            }
+        }],
+    });
+
+    if let Some(fix) = fix {
+        result["fixes"] = json!([fix]);
+    }
+
+    result
+}
+
+/// The change `Linter::format` would make to the file, as the smallest run of replaced lines.
+fn fix(linter: &Linter, filename: &Path, content: String, uri: &str) -> Option<Value> {
+    let formatted_content = linter.format(filename, &content);
+
+    if formatted_content == content {
+        return None;
+    }
+
+    let old_lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = formatted_content.split_inclusive('\n').collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let deleted_end = old_lines.len() - suffix;
+    let inserted = new_lines[prefix..new_lines.len() - suffix].concat();
+    let (start_line, start_column) = line_start(&old_lines, prefix);
+    let (end_line, end_column) = line_start(&old_lines, deleted_end);
+
+    Some(json!({
+        "description": { "text": "Insert the expected license header" },
+        "artifactChanges": [{
+            "artifactLocation": { "uri": uri, "uriBaseId": "%SRCROOT%" },
+            "replacements": [{
+                "deletedRegion": {
+                    "startLine": start_line,
+                    "startColumn": start_column,
+                    "endLine": end_line,
+                    "endColumn": end_column,
+                },
+                "insertedContent": { "text": inserted },
+            }]
+        }]
+    }))
+}
+
+/// The 1-based line and column where the line at `index` starts. Past the end of a file without a
+/// trailing newline, that is the column after its last character, as SARIF has no line after it.
+fn line_start(lines: &[&str], index: usize) -> (usize, usize) {
+    match index.checked_sub(1).map(|previous| lines[previous]) {
+        Some(previous) if !previous.ends_with('\n') => (index, previous.chars().count() + 1),
+        _ => (index + 1, 1),
+    }
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+fn relative_uri(root: &Path, path: &Path) -> String {
+    encode_path(path.strip_prefix(root).unwrap_or(path))
+}
+
+fn directory_uri(root: &Path) -> String {
+    format!("file:///{}/", encode_path(root))
+}
+
+fn encode_path(path: &Path) -> String {
+    let segments: Vec<String> = slash_path::segments(path)
+        .iter()
+        .map(|segment| percent_encode(segment))
+        .collect();
+    segments.join("/")
+}
+
+fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::new();
+
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use licenselint::config::{Config, HeaderStyle};
+    use licenselint::issue::Span;
+    use licenselint::license::License;
+
+    fn config() -> Config {
+        let mut config =
+            Config::new_from_author(License::Mit, "Your Name".to_string(), "2024".to_string());
+        config.header_style = HeaderStyle::Spdx;
+        config
+    }
+
+    fn replacement(content: &str) -> Value {
+        let config = config();
+        let linter = Linter::new(&config).unwrap();
+        let fix = fix(
+            &linter,
+            Path::new("src/main.rs"),
+            content.to_string(),
+            "src/main.rs",
+        )
+        .expect("the file should need a fix");
+        fix["artifactChanges"][0]["replacements"][0].clone()
+    }
+
+    #[test]
+    fn test_fix_insertion() {
+        assert_eq!(
+            replacement("fn main() {}\n"),
+            json!({
+                "deletedRegion": { "startLine": 1, "startColumn": 1, "endLine": 1, "endColumn": 1 },
+                "insertedContent": {
+                    "text": "// SPDX-FileCopyrightText: 2024 Your Name\n// SPDX-License-Identifier: MIT\n\n"
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_fix_replacement() {
+        let content = "// SPDX-FileCopyrightText: 2020 Someone\n// SPDX-License-Identifier: MIT\n\nfn main() {}\n";
+        assert_eq!(
+            replacement(content),
+            json!({
+                "deletedRegion": { "startLine": 1, "startColumn": 1, "endLine": 2, "endColumn": 1 },
+                "insertedContent": { "text": "// SPDX-FileCopyrightText: 2020 Your Name\n" },
+            })
+        );
+    }
+
+    #[test]
+    fn test_fix_without_trailing_newline() {
+        let content =
+            "// SPDX-FileCopyrightText: 2020 Someone\n// SPDX-License-Identifier: Apache-2.0";
+        assert_eq!(
+            replacement(content),
+            json!({
+                "deletedRegion": { "startLine": 1, "startColumn": 1, "endLine": 2, "endColumn": 39 },
+                "insertedContent": {
+                    "text": "// SPDX-FileCopyrightText: 2024 Your Name\n// SPDX-License-Identifier: MIT"
+                },
+            })
+        );
+        assert_eq!(
+            replacement("")["deletedRegion"],
+            json!({ "startLine": 1, "startColumn": 1, "endLine": 1, "endColumn": 1 }),
+            "An empty file has an empty first line"
+        );
+    }
+
+    #[test]
+    fn test_rule_index() {
+        let report = CheckReport {
+            issues: IssueKind::ALL
+                .iter()
+                .map(|kind| {
+                    Issue::new(
+                        Path::new("/repo/src/main.rs"),
+                        *kind,
+                        Span::line(1, "fn main() {}"),
+                        kind.description().to_string(),
+                    )
+                })
+                .collect(),
+            ..CheckReport::default()
+        };
+
+        let config = config();
+        let linter = Linter::new(&config).unwrap();
+        let log: Value =
+            serde_json::from_str(&render(&report, Path::new("/repo"), &linter, &|_| None)).unwrap();
+        let run = &log["runs"][0];
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), IssueKind::ALL.len());
+        for result in results {
+            let rule =
+                &run["tool"]["driver"]["rules"][result["ruleIndex"].as_u64().unwrap() as usize];
+            assert_eq!(rule["id"], result["ruleId"]);
+            assert_eq!(
+                result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+                "src/main.rs"
+            );
+        }
+    }
+}
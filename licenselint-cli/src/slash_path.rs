@@ -0,0 +1,40 @@
+use std::path::{Component, Path};
+
+/// The segments of `path` without its root, so they can be joined with `/` on every platform.
+pub fn segments(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy().into_owned()),
+            Component::ParentDir => Some("..".to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `path` with `/` separators, as patches, baselines and reports expect on every platform.
+pub fn to_slash(path: &Path) -> String {
+    segments(path).join("/")
+}
+
+/// `path` relative to `root` with `/` separators, or all of `path` when it is outside `root`.
+pub fn relative_to_slash(root: &Path, path: &Path) -> String {
+    to_slash(path.strip_prefix(root).unwrap_or(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_to_slash() {
+        assert_eq!(
+            relative_to_slash(Path::new("/repo"), Path::new("/repo/src/./main.rs")),
+            "src/main.rs"
+        );
+        assert_eq!(
+            relative_to_slash(Path::new("/repo"), Path::new("/other/../main.rs")),
+            "other/../main.rs",
+            "Paths outside the root should be kept whole"
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use tempfile::TempDir;
+
+/// A directory with a `licenselint.toml`, removed when dropped.
+struct Project(TempDir);
+
+impl Project {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("licenselint.toml"),
+            "author = \"Your Name\"\nheader-style = \"spdx\"\n",
+        )
+        .unwrap();
+        Project(dir)
+    }
+
+    fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    /// Runs licenselint in the project with `stdin` as its standard input.
+    fn run(&self, args: &[&str], stdin: &str) -> Output {
+        self.run_in(".", args, stdin)
+    }
+
+    /// Runs licenselint in the directory `dir` of the project.
+    fn run_in(&self, dir: &str, args: &[&str], stdin: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_licenselint"))
+            .current_dir(self.path().join(dir))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_check_stdin_picks_template_by_filename() {
+    let project = Project::new();
+    let args = |filename| {
+        [
+            "check",
+            "--format",
+            "json",
+            "--stdin",
+            "--stdin-filename",
+            filename,
+        ]
+    };
+
+    let output = project.run(&args("notes.unknown"), "no header\n");
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["summary"]["files_scanned"], 0);
+    assert_eq!(
+        report["summary"]["files_skipped"], 1,
+        "Content for a file without a template should be skipped"
+    );
+
+    let output = project.run(&args("src/main.rs"), "fn main() {}\n");
+    assert_eq!(output.status.code(), Some(2), "{}", stdout(&output));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["summary"]["languages"]["rust"], 1);
+    assert_eq!(report["issues"][0]["file"], "src/main.rs");
+    assert_eq!(report["issues"][0]["kind"], "missing-header");
+}
+
+#[test]
+fn test_format_stdin() {
+    let project = Project::new();
+
+    let output = project.run(
+        &["format", "--stdin", "--stdin-filename", "notes.unknown"],
+        "no header\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "no header\n");
+
+    let output = project.run(
+        &["format", "--stdin", "--stdin-filename", "src/main.rs"],
+        "fn main() {}\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("// SPDX-FileCopyrightText: "));
+    assert!(stdout(&output)
+        .ends_with(" Your Name\n// SPDX-License-Identifier: Apache-2.0\n\nfn main() {}\n"));
+}
+
+#[test]
+fn test_format_check() {
+    let project = Project::new();
+    let file = project.path().join("main.rs");
+    fs::write(&file, "fn main() {}\n").unwrap();
+
+    let output = project.run(&["format", "--check"], "");
+    assert_eq!(output.status.code(), Some(2), "{}", stdout(&output));
+    assert!(stdout(&output).contains("Would format main.rs\n"));
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "fn main() {}\n",
+        "format --check should not write files"
+    );
+
+    assert_eq!(project.run(&["format"], "").status.code(), Some(0));
+
+    let output = project.run(&["format", "--check"], "");
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert_eq!(stdout(&output), "All files are formatted.\n");
+}
+
+#[test]
+fn test_parent_dir_argument() {
+    let project = Project::new();
+    fs::write(
+        project.path().join("licenselint.toml"),
+        "author = \"Your Name\"\nheader-style = \"spdx\"\nexclude = [\"gen/**\"]\n\n\
+         [[overrides]]\npath = \"third_party/**\"\nlicense = \"MIT\"\n",
+    )
+    .unwrap();
+    for file in ["src/a.rs", "gen/g.rs", "third_party/b.rs"] {
+        let file = project.path().join(file);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, "fn main() {}\n").unwrap();
+    }
+
+    let output = project.run_in("src", &["format", "../third_party"], "");
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert!(
+        fs::read_to_string(project.path().join("third_party/b.rs"))
+            .unwrap()
+            .contains("// SPDX-License-Identifier: MIT\n"),
+        "Overrides should apply to paths given with .."
+    );
+
+    let output = project.run_in("src", &["check", "--format", "json", ".."], "");
+    assert_eq!(output.status.code(), Some(2), "{}", stdout(&output));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let files: Vec<&str> = report["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|issue| issue["file"].as_str().unwrap())
+        .filter(|file| file.ends_with(".rs"))
+        .collect();
+    assert_eq!(
+        files,
+        vec!["src/a.rs"],
+        "Excludes should apply to paths given with .."
+    );
+}
+
+#[test]
+fn test_default_baseline_from_subdirectory() {
+    let project = Project::new();
+    fs::create_dir_all(project.path().join("src")).unwrap();
+    fs::write(project.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+
+    let output = project.run_in("src", &["check", "--write-baseline"], "");
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert!(
+        project.path().join(".licenselint-baseline.json").exists(),
+        "The default baseline should be next to licenselint.toml"
+    );
+
+    let output = project.run_in("src", &["check", "--baseline"], "");
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert_eq!(stdout(&output), "No issues found.\n");
+}
+
+#[test]
+fn test_author_flag_replaces_email() {
+    let project = Project::new();
+    fs::write(
+        project.path().join("licenselint.toml"),
+        "author = \"Company\"\nemail = \"oss@company.com\"\nheader-style = \"spdx\"\n",
+    )
+    .unwrap();
+    let format = ["format", "--stdin", "--stdin-filename", "main.rs"];
+
+    let output = project.run(
+        &[&["--author", "Bob"][..], &format].concat(),
+        "fn main() {}\n",
+    );
+    assert!(
+        stdout(&output).contains(" Bob\n"),
+        "The email of the file should not be kept for another author: {}",
+        stdout(&output)
+    );
+
+    let output = project.run(
+        &[
+            &["--author", "Bob", "--email", "bob@example.com"][..],
+            &format,
+        ]
+        .concat(),
+        "fn main() {}\n",
+    );
+    assert!(stdout(&output).contains(" Bob <bob@example.com>\n"));
+}
+
+#[test]
+fn test_stdin_year_of_committed_file() {
+    let project = Project::new();
+    fs::write(
+        project.path().join("licenselint.toml"),
+        "author = \"Your Name\"\nheader-style = \"spdx\"\n\n[year]\ncover-last-modified = true\n",
+    )
+    .unwrap();
+    let content =
+        "// SPDX-FileCopyrightText: 2020 Your Name\n// SPDX-License-Identifier: Apache-2.0\n";
+    fs::create_dir_all(project.path().join("src")).unwrap();
+    fs::write(project.path().join("src/a.rs"), content).unwrap();
+
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .current_dir(project.path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .env("GIT_COMMITTER_DATE", "2020-06-01T12:00:00Z")
+            .env("GIT_AUTHOR_DATE", "2020-06-01T12:00:00Z")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "Add a"]);
+
+    let output = project.run_in("src", &["check", "a.rs"], "");
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+
+    let output = project.run_in(
+        "src",
+        &["check", "--stdin", "--stdin-filename", "a.rs"],
+        content,
+    );
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "The year should be looked up for the file in the project: {}",
+        stdout(&output)
+    );
+}
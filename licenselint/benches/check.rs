@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use licenselint::config::Config;
+use licenselint::license::License;
+use licenselint::linter::Linter;
+use std::path::{Path, PathBuf};
+
+const FILE_COUNTS: [usize; 3] = [100, 1_000, 10_000];
+
+fn config() -> Config {
+    Config::new_from_author(
+        License::Apache20,
+        "ZhangJian He".to_string(),
+        "1997".to_string(),
+    )
+}
+
+/// The time per file should stay flat as the number of files grows.
+fn bench_check(c: &mut Criterion) {
+    let config = config();
+    let linter = Linter::new(&config).unwrap();
+    let licensed = linter.format(Path::new("src/main.rs"), "fn main() {}\n");
+    let unlicensed = "fn main() {}\n".to_string();
+
+    let mut group = c.benchmark_group("check");
+
+    for (name, content) in [("licensed", &licensed), ("unlicensed", &unlicensed)] {
+        for file_count in FILE_COUNTS {
+            group.throughput(Throughput::Elements(file_count as u64));
+            group.bench_with_input(BenchmarkId::new(name, file_count), &file_count, |b, &n| {
+                b.iter(|| {
+                    for i in 0..n {
+                        let filename = PathBuf::from(format!("src/file{}.rs", i));
+                        black_box(linter.check(&filename, black_box(content)));
+                    }
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_check);
+criterion_main!(benches);
@@ -1,23 +1,510 @@
+use crate::error::{Error, Result};
 use crate::license::License;
+use chrono::{Datelike, Local};
+use globset::Glob;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HeaderStyle {
+    /// The full license boilerplate, e.g. the 13-line Apache-2.0 notice.
+    Full,
+    /// `SPDX-FileCopyrightText` and `SPDX-License-Identifier` short-form lines.
+    Spdx,
+}
+
+impl FromStr for HeaderStyle {
+    type Err = Error;
+
+    fn from_str(style: &str) -> Result<Self> {
+        match style {
+            "full" => Ok(HeaderStyle::Full),
+            "spdx" => Ok(HeaderStyle::Spdx),
+            _ => Err(Error::invalid_config(format!(
+                "unsupported header style '{}', expected 'full' or 'spdx'",
+                style
+            ))),
+        }
+    }
+}
+
+/// What `format` does with a license header that does not pass the check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExistingHeader {
+    /// Rewrite the author of an otherwise matching header in place, keeping its year, and
+    /// replace any other license header.
+    Update,
+    /// Always replace the existing license header with a new one.
+    Replace,
+}
+
+impl FromStr for ExistingHeader {
+    type Err = Error;
+
+    fn from_str(action: &str) -> Result<Self> {
+        match action {
+            "update" => Ok(ExistingHeader::Update),
+            "replace" => Ok(ExistingHeader::Replace),
+            _ => Err(Error::invalid_config(format!(
+                "unsupported existing header action '{}', expected 'update' or 'replace'",
+                action
+            ))),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Config {
     pub license: License,
+    pub header_style: HeaderStyle,
+    pub existing_header: ExistingHeader,
     pub allowed_authors: Vec<String>,
     pub formatted_author: String,
     pub formatted_year: String,
+    /// The copyright years `check` accepts.
+    pub year_policy: YearPolicy,
+    /// Globs, relative to the linted directory, a file must match one of to be linted.
+    /// Every file is linted when empty.
+    pub include: Vec<String>,
+    /// Globs, relative to the linted directory, of files that are never linted. A glob matching a
+    /// directory excludes everything in it.
+    pub exclude: Vec<String>,
+    /// Whether `DEFAULT_EXCLUDE` is excluded as well.
+    pub default_exclude: bool,
+    /// Extra file extensions linted like a known extension, e.g. `mjs` -> `ts`.
+    pub extensions: HashMap<String, String>,
+    /// Path-scoped settings, the last override matching a file wins.
+    pub overrides: Vec<Override>,
+    /// Files larger than this many bytes are skipped.
+    pub max_file_size: u64,
+}
+
+/// Bounds on the copyright years `check` accepts, which are single years, ranges like `2019-2024`
+/// or comma separated lists of both.
+#[derive(Clone, Debug, Default)]
+pub struct YearPolicy {
+    /// The earliest year allowed, usually the year the project started.
+    pub start: Option<i32>,
+    /// The latest year allowed, the current year unless future years are allowed.
+    pub end: Option<i32>,
+    /// Whether the last year must be at least the year the file was last modified.
+    pub cover_last_modified: bool,
+}
+
+/// Settings that replace the `Config` ones for files matching `path`.
+#[derive(Clone)]
+pub struct Override {
+    /// A glob relative to the linted directory, e.g. `third_party/**`.
+    pub path: String,
+    pub license: Option<License>,
+    pub header_style: Option<HeaderStyle>,
+    /// Replaces both the formatted author and the allowed authors.
+    pub formatted_author: Option<String>,
+    pub allowed_authors: Vec<String>,
 }
 
 impl Config {
+    pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+    /// Build output, dependencies and lockfiles, which are generated rather than written.
+    pub const DEFAULT_EXCLUDE: [&'static str; 11] = [
+        "**/target",
+        "**/node_modules",
+        "**/dist",
+        "**/Cargo.lock",
+        "**/package-lock.json",
+        "**/yarn.lock",
+        "**/pnpm-lock.yaml",
+        "**/poetry.lock",
+        "**/Gemfile.lock",
+        "**/composer.lock",
+        "**/go.sum",
+    ];
+
     pub fn new_from_author(license: License, author: String, formatted_year: String) -> Self {
         Config {
             license,
+            header_style: HeaderStyle::Full,
+            existing_header: ExistingHeader::Update,
             allowed_authors: vec![author.clone()],
             formatted_author: author,
             formatted_year,
+            year_policy: YearPolicy {
+                end: Some(Local::now().year()),
+                ..YearPolicy::default()
+            },
+            include: Vec::new(),
+            exclude: Vec::new(),
+            default_exclude: true,
+            extensions: HashMap::new(),
+            overrides: Vec::new(),
+            max_file_size: Self::DEFAULT_MAX_FILE_SIZE,
         }
     }
 
     pub fn add_allowed_author(&mut self, author: String) {
         self.allowed_authors.push(author);
     }
+
+    /// `exclude` followed by `DEFAULT_EXCLUDE` unless disabled.
+    pub fn exclude_globs(&self) -> Vec<String> {
+        let mut globs = self.exclude.clone();
+
+        if self.default_exclude {
+            globs.extend(Self::DEFAULT_EXCLUDE.iter().map(|glob| glob.to_string()));
+        }
+
+        globs
+    }
+
+    /// The effective config for files matched by `over`.
+    pub fn with_override(&self, over: &Override) -> Config {
+        let mut config = self.clone();
+
+        if let Some(license) = over.license {
+            config.license = license;
+        }
+
+        if let Some(header_style) = over.header_style {
+            config.header_style = header_style;
+        }
+
+        if let Some(author) = &over.formatted_author {
+            config.formatted_author = author.clone();
+            config.allowed_authors = vec![author.clone()];
+        }
+
+        config
+            .allowed_authors
+            .extend(over.allowed_authors.iter().cloned());
+        config.overrides = Vec::new();
+
+        config
+    }
+}
+
+/// The contents of a `licenselint.toml` project configuration file.
+///
+/// ```toml
+/// license = "MIT"
+/// author = "Your Company"
+/// email = "oss@example.com"
+/// allowed-authors = ["Partner Inc."]
+/// header-style = "spdx"
+/// existing-header = "update"
+/// include = ["src/**"]
+/// exclude = ["src/generated/**"]
+/// default-exclude = true
+/// max-file-size = 1048576
+///
+/// [year]
+/// insert = "2024"
+/// start = 2019
+/// cover-last-modified = true
+///
+/// [extensions]
+/// mjs = "ts"
+///
+/// [[overrides]]
+/// path = "third_party/**"
+/// license = "Apache-2.0"
+/// author = "Upstream Authors"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FileConfig {
+    pub license: Option<String>,
+    pub author: Option<String>,
+    pub email: Option<String>,
+    pub allowed_authors: Vec<String>,
+    pub header_style: Option<String>,
+    pub existing_header: Option<String>,
+    pub year: YearConfig,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    /// Whether `Config::DEFAULT_EXCLUDE` is excluded as well, `true` by default.
+    pub default_exclude: Option<bool>,
+    pub extensions: HashMap<String, String>,
+    pub overrides: Vec<OverrideConfig>,
+    /// In bytes, defaults to `Config::DEFAULT_MAX_FILE_SIZE`.
+    pub max_file_size: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct OverrideConfig {
+    pub path: String,
+    pub license: Option<String>,
+    pub author: Option<String>,
+    pub email: Option<String>,
+    pub allowed_authors: Vec<String>,
+    pub header_style: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct YearConfig {
+    /// The year `format` inserts, `current` or a fixed year. Defaults to the current year.
+    pub insert: Option<String>,
+    /// The earliest copyright year allowed, usually the year the project started.
+    pub start: Option<i32>,
+    /// Whether copyright years after the current year are allowed, `false` by default.
+    pub allow_future: Option<bool>,
+    /// Whether the last copyright year must be at least the year the file was last modified,
+    /// `false` by default.
+    pub cover_last_modified: Option<bool>,
+}
+
+impl FileConfig {
+    pub const FILE_NAME: &'static str = "licenselint.toml";
+
+    /// Looks for `licenselint.toml` in `dir` and its parents, stopping at the repository root.
+    pub fn discover(dir: &Path) -> Option<PathBuf> {
+        for ancestor in dir.ancestors() {
+            let candidate = ancestor.join(Self::FILE_NAME);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+
+            if ancestor.join(".git").exists() {
+                break;
+            }
+        }
+
+        None
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+
+        Self::parse(&content).map_err(|e| match e {
+            Error::InvalidConfig {
+                path: None,
+                message,
+            } => Error::InvalidConfig {
+                path: Some(path.to_path_buf()),
+                message,
+            },
+            e => e,
+        })
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content)
+            .map_err(|e| Error::invalid_config(e.to_string().trim_end().to_string()))
+    }
+
+    pub fn into_config(self) -> Result<Config> {
+        let license = License::from_str(self.license.as_deref().unwrap_or("Apache-2.0"))?;
+        let header_style = HeaderStyle::from_str(self.header_style.as_deref().unwrap_or("full"))?;
+        let existing_header =
+            ExistingHeader::from_str(self.existing_header.as_deref().unwrap_or("update"))?;
+
+        let author = self.author.unwrap_or_else(|| "Unknown Author".to_string());
+        let formatted_author = format_author(author, self.email);
+
+        let current_year = Local::now().year();
+        let inserted_year = match self.year.insert.as_deref() {
+            None | Some("current") => current_year,
+            Some(year) if year.len() == 4 && year.bytes().all(|b| b.is_ascii_digit()) => {
+                year.parse().expect("four digits are a valid year")
+            }
+            Some(year) => {
+                return Err(Error::invalid_config(format!(
+                    "invalid year.insert '{}', expected 'current' or a year like '2024'",
+                    year
+                )))
+            }
+        };
+        if let Some(start) = self.year.start.filter(|&start| inserted_year < start) {
+            return Err(Error::invalid_config(format!(
+                "year.insert {} is before year.start {}",
+                inserted_year, start
+            )));
+        }
+        if inserted_year > current_year && !self.year.allow_future.unwrap_or(false) {
+            return Err(Error::invalid_config(format!(
+                "year.insert {} is in the future, set year.allow-future to use it",
+                inserted_year
+            )));
+        }
+        let formatted_year = inserted_year.to_string();
+
+        let mut config = Config::new_from_author(license, formatted_author, formatted_year);
+        config.header_style = header_style;
+        config.existing_header = existing_header;
+        config.year_policy.start = self.year.start;
+        config.year_policy.cover_last_modified = self.year.cover_last_modified.unwrap_or(false);
+        if self.year.allow_future.unwrap_or(false) {
+            config.year_policy.end = None;
+        }
+        config.allowed_authors.extend(self.allowed_authors);
+        config.include = self.include;
+        config.exclude = self.exclude;
+        config.default_exclude = self.default_exclude.unwrap_or(true);
+        config.extensions = self.extensions;
+
+        if let Some(max_file_size) = self.max_file_size {
+            config.max_file_size = max_file_size;
+        }
+
+        for over in self.overrides {
+            Glob::new(&over.path).map_err(|e| Error::invalid_config(e.to_string()))?;
+            if over.author.is_none() && over.email.is_some() {
+                return Err(Error::invalid_config(format!(
+                    "override '{}' sets email without author",
+                    over.path
+                )));
+            }
+
+            config.overrides.push(Override {
+                path: over.path,
+                license: over.license.as_deref().map(License::from_str).transpose()?,
+                header_style: over
+                    .header_style
+                    .as_deref()
+                    .map(HeaderStyle::from_str)
+                    .transpose()?,
+                formatted_author: over.author.map(|author| format_author(author, over.email)),
+                allowed_authors: over.allowed_authors,
+            });
+        }
+
+        Ok(config)
+    }
+}
+
+fn format_author(author: String, email: Option<String>) -> String {
+    match email {
+        Some(email) => format!("{} <{}>", author, email),
+        None => author,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_config() {
+        let content = r#"
+license = "MIT"
+author = "ZhangJian He"
+email = "zhangjian@example.com"
+allowed-authors = ["Partner Inc."]
+header-style = "spdx"
+exclude = ["third_party/**"]
+default-exclude = false
+max-file-size = 4096
+
+[year]
+insert = "1997"
+start = 1990
+allow-future = true
+cover-last-modified = true
+
+[extensions]
+mjs = "ts"
+"#;
+
+        let config = FileConfig::parse(content).unwrap().into_config().unwrap();
+        assert_eq!(config.license.to_str(), "MIT");
+        assert_eq!(config.header_style, HeaderStyle::Spdx);
+        assert_eq!(
+            config.formatted_author,
+            "ZhangJian He <zhangjian@example.com>"
+        );
+        assert!(config.allowed_authors.contains(&"Partner Inc.".to_string()));
+        assert_eq!(config.formatted_year, "1997");
+        assert_eq!(config.year_policy.start, Some(1990));
+        assert_eq!(config.year_policy.end, None);
+        assert!(config.year_policy.cover_last_modified);
+        assert_eq!(config.exclude, vec!["third_party/**".to_string()]);
+        assert_eq!(config.exclude_globs(), vec!["third_party/**".to_string()]);
+        assert_eq!(config.extensions.get("mjs"), Some(&"ts".to_string()));
+        assert_eq!(config.max_file_size, 4096);
+    }
+
+    #[test]
+    fn test_parse_overrides() {
+        let content = r#"
+author = "ZhangJian He"
+
+[[overrides]]
+path = "third_party/**"
+license = "MIT"
+author = "Upstream Authors"
+header-style = "spdx"
+"#;
+
+        let config = FileConfig::parse(content).unwrap().into_config().unwrap();
+        assert_eq!(config.overrides.len(), 1);
+
+        let effective = config.with_override(&config.overrides[0]);
+        assert_eq!(effective.license, License::Mit);
+        assert_eq!(effective.header_style, HeaderStyle::Spdx);
+        assert_eq!(effective.formatted_author, "Upstream Authors");
+        assert_eq!(
+            effective.allowed_authors,
+            vec!["Upstream Authors".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_file_config() {
+        assert!(FileConfig::parse("unknown-key = true").is_err());
+        assert!(FileConfig::parse(r#"license = "GPL""#)
+            .unwrap()
+            .into_config()
+            .is_err());
+        assert!(FileConfig::parse("[[overrides]]\npath = \"a/[\"")
+            .unwrap()
+            .into_config()
+            .is_err());
+
+        let error = FileConfig::parse("[[overrides]]\npath = \"vendor/**\"\nemail = \"a@b.c\"")
+            .unwrap()
+            .into_config()
+            .err()
+            .expect("an override email without author should be rejected");
+        assert_eq!(
+            error.message(),
+            "override 'vendor/**' sets email without author"
+        );
+
+        for (year, message) in [
+            (
+                "insert = \"97\"",
+                "invalid year.insert '97', expected 'current' or a year like '2024'",
+            ),
+            (
+                "insert = \"2024-2025\"",
+                "invalid year.insert '2024-2025', expected 'current' or a year like '2024'",
+            ),
+            (
+                "insert = \"1989\"\nstart = 1990",
+                "year.insert 1989 is before year.start 1990",
+            ),
+            (
+                "insert = \"9999\"",
+                "year.insert 9999 is in the future, set year.allow-future to use it",
+            ),
+        ] {
+            let error = FileConfig::parse(&format!("[year]\n{}", year))
+                .unwrap()
+                .into_config()
+                .err()
+                .unwrap_or_else(|| panic!("'{}' should be rejected", year));
+            assert_eq!(error.message(), message);
+        }
+
+        let config = FileConfig::parse("[year]\ninsert = \"9999\"\nallow-future = true")
+            .unwrap()
+            .into_config()
+            .unwrap();
+        assert_eq!(config.formatted_year, "9999");
+    }
 }
@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+use std::{fmt, io};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing `path` failed.
+    Io { path: PathBuf, source: io::Error },
+    /// Walking a directory failed, e.g. on a broken symlink or an unreadable ignore file.
+    Walk {
+        path: Option<PathBuf>,
+        source: ignore::Error,
+    },
+    /// The content of `path` is not valid UTF-8.
+    InvalidUtf8 { path: PathBuf },
+    /// A license name that is not supported, with the supported names closest to it.
+    UnknownLicense {
+        name: String,
+        suggestions: Vec<&'static str>,
+    },
+    /// A configuration value that cannot be used, from the file at `path` if any.
+    InvalidConfig {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// The template for `language` cannot be built for the configuration.
+    Template { language: String, message: String },
+    /// Running git in the repository at `path` failed.
+    Git { path: PathBuf, message: String },
+}
+
+impl Error {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        Error::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn invalid_config(message: String) -> Self {
+        Error::InvalidConfig {
+            path: None,
+            message,
+        }
+    }
+
+    /// The file or directory the error is about.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::Io { path, .. } | Error::InvalidUtf8 { path } | Error::Git { path, .. } => {
+                Some(path)
+            }
+            Error::Walk { path, .. } | Error::InvalidConfig { path, .. } => path.as_deref(),
+            Error::UnknownLicense { .. } | Error::Template { .. } => None,
+        }
+    }
+
+    /// The error without the path it is about, for output that reports the path separately.
+    pub fn message(&self) -> String {
+        Message(self).to_string()
+    }
+}
+
+struct Message<'a>(&'a Error);
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Error::Io { source, .. } => write!(f, "{}", source),
+            Error::Walk { source, .. } => write!(f, "{}", source),
+            Error::InvalidUtf8 { .. } => f.write_str("not valid UTF-8"),
+            Error::UnknownLicense { name, suggestions } => {
+                write!(f, "unsupported license '{}'", name)?;
+                match suggestions.as_slice() {
+                    [] => Ok(()),
+                    [suggestion] => write!(f, ", did you mean '{}'?", suggestion),
+                    suggestions => write!(f, ", did you mean one of {}?", suggestions.join(", ")),
+                }
+            }
+            Error::InvalidConfig { message, .. } | Error::Git { message, .. } => {
+                f.write_str(message)
+            }
+            Error::Template { language, message } => {
+                write!(f, "invalid {} template: {}", language, message)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = self.path() {
+            write!(f, "{}: ", path.display())?;
+        }
+
+        Message(self).fmt(f)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Walk { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
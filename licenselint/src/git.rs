@@ -0,0 +1,392 @@
+use crate::error::{Error, Result};
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, RwLock};
+
+/// Files added, copied, modified or renamed in the working tree of the repository at `dir` since
+/// `reference`, plus untracked files that are not ignored. Paths are relative to `dir`, and files
+/// outside it are left out.
+pub fn changed_since(dir: &Path, reference: &str) -> Result<Vec<PathBuf>> {
+    let mut files = git(
+        dir,
+        &[
+            "diff",
+            "--name-only",
+            "--relative",
+            "--diff-filter=ACMR",
+            "-z",
+            reference,
+            "--",
+        ],
+    )?;
+    files.extend(git(
+        dir,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?);
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+/// Files added, copied, modified or renamed in the index of the repository at `dir`, relative to
+/// `dir`.
+pub fn staged(dir: &Path) -> Result<Vec<PathBuf>> {
+    git(
+        dir,
+        &[
+            "diff",
+            "--name-only",
+            "--relative",
+            "--diff-filter=ACMR",
+            "-z",
+            "--cached",
+        ],
+    )
+}
+
+/// The years of the last commits of files, read with one `git status` and one `git log` per
+/// repository for the paths passed to `load`, and per file for the other files looked up.
+#[derive(Default)]
+pub struct CommitYears {
+    /// The last commit year of files by canonical path, `None` for files with uncommitted changes.
+    years: RwLock<HashMap<PathBuf, Option<i32>>>,
+    /// The canonical paths loaded so far, files in them missing from `years` are not committed.
+    loaded: RwLock<Vec<PathBuf>>,
+    /// Held while loading, so threads looking up the same file do not all run git.
+    loading: Mutex<()>,
+}
+
+impl CommitYears {
+    /// The year of the last commit that changed `path`, or `None` when the file is not in a git
+    /// repository, is untracked or has uncommitted changes.
+    pub fn last_commit_year(&self, path: &Path) -> Option<i32> {
+        let path = fs::canonicalize(path).ok()?;
+
+        if !self.is_loaded(&path) {
+            let _loading = self.loading.lock().unwrap();
+            if !self.is_loaded(&path) {
+                self.read(std::slice::from_ref(&path));
+            }
+        }
+
+        self.years.read().unwrap().get(&path).copied().flatten()
+    }
+
+    /// Reads the commit years of the files and directories `paths` ahead of their lookups, with one
+    /// git run per repository rather than per file. Files written afterwards keep the year of
+    /// their last commit.
+    pub fn load(&self, paths: &[PathBuf]) {
+        let _loading = self.loading.lock().unwrap();
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .filter_map(|path| fs::canonicalize(path).ok())
+            .filter(|path| !self.is_loaded(path))
+            .collect();
+
+        self.read(&paths);
+    }
+
+    fn is_loaded(&self, path: &Path) -> bool {
+        self.loaded
+            .read()
+            .unwrap()
+            .iter()
+            .any(|loaded| path.starts_with(loaded))
+    }
+
+    /// Reads the commit years of the canonical `paths`, grouped by repository.
+    fn read(&self, paths: &[PathBuf]) {
+        let mut repos: HashMap<&Path, Vec<&Path>> = HashMap::new();
+        for path in paths {
+            if let Some(repo) = path.ancestors().find(|dir| dir.join(".git").exists()) {
+                repos
+                    .entry(repo)
+                    .or_default()
+                    .push(path.strip_prefix(repo).unwrap());
+            }
+        }
+
+        for (repo, relative_paths) in repos {
+            // Keeps each command line well below the limits of every platform.
+            for chunk in relative_paths.chunks(1000) {
+                if let Ok(years) = read_commit_years(repo, chunk) {
+                    self.years.write().unwrap().extend(
+                        years
+                            .into_iter()
+                            .map(|(path, year)| (repo.join(path), year)),
+                    );
+                }
+            }
+        }
+
+        self.loaded.write().unwrap().extend(paths.iter().cloned());
+    }
+}
+
+/// The year of the last commit of every committed file in `paths` of the repository at `repo`,
+/// `None` for files with uncommitted changes, relative to `repo`.
+fn read_commit_years(repo: &Path, paths: &[&Path]) -> Result<HashMap<PathBuf, Option<i32>>> {
+    let pathspecs: Vec<OsString> = if paths.iter().any(|path| path.as_os_str().is_empty()) {
+        Vec::new()
+    } else {
+        paths
+            .iter()
+            .map(|path| {
+                let mut pathspec = OsString::from(":(literal)");
+                pathspec.push(path);
+                pathspec
+            })
+            .collect()
+    };
+
+    let mut years = HashMap::new();
+
+    let mut status_args = vec![
+        OsString::from("status"),
+        "--porcelain".into(),
+        "-z".into(),
+        "--untracked-files=no".into(),
+        "--".into(),
+    ];
+    status_args.extend(pathspecs.iter().cloned());
+    let status = run(repo, &status_args)?;
+    let mut entries = status
+        .split(|&byte| byte == 0)
+        .filter(|entry| entry.len() > 3);
+    while let Some(entry) = entries.next() {
+        years.insert(path_from_bytes(repo, &entry[3..])?, None);
+        // Renames and copies are followed by the path they come from.
+        if matches!(entry[0], b'R' | b'C') {
+            entries.next();
+        }
+    }
+
+    // When only files are looked up, the log can stop as soon as each of them has a year.
+    let mut pending: Option<HashSet<PathBuf>> =
+        paths.iter().all(|path| repo.join(path).is_file()).then(|| {
+            paths
+                .iter()
+                .map(|path| path.to_path_buf())
+                .filter(|path| !years.contains_key(path))
+                .collect()
+        });
+    if pending.as_ref().is_some_and(HashSet::is_empty) {
+        return Ok(years);
+    }
+
+    // Each commit, newest first, is a `\x01<year>` entry followed by the files it changed.
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "log",
+            "-z",
+            "--name-only",
+            "--format=%x01%cd",
+            "--date=format:%Y",
+            "--",
+        ])
+        .args(&pathspecs)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| Error::Git {
+            path: repo.to_path_buf(),
+            message: format!("cannot run git: {}", e),
+        })?;
+
+    let mut log = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let mut entry = Vec::new();
+    let mut year = None;
+    while log.read_until(0, &mut entry).unwrap_or(0) > 0 {
+        let bytes = entry.strip_suffix(b"\0").unwrap_or(&entry);
+
+        if let Some(commit_year) = bytes.strip_prefix(b"\x01") {
+            year = std::str::from_utf8(commit_year)
+                .ok()
+                .and_then(|year| year.parse().ok());
+        } else {
+            let bytes = bytes.strip_prefix(b"\n").unwrap_or(bytes);
+            if let (Some(year), false) = (year, bytes.is_empty()) {
+                let path = path_from_bytes(repo, bytes)?;
+                if let Some(pending) = &mut pending {
+                    pending.remove(&path);
+                }
+                years.entry(path).or_insert(Some(year));
+            }
+        }
+
+        entry.clear();
+        if pending.as_ref().is_some_and(HashSet::is_empty) {
+            let _ = child.kill();
+            break;
+        }
+    }
+    // A repository without commits has no log, which leaves every file uncommitted.
+    let _ = child.wait();
+
+    Ok(years)
+}
+
+/// Runs git in `dir` and returns its standard output.
+fn run<S: AsRef<OsStr>>(dir: &Path, args: &[S]) -> Result<Vec<u8>> {
+    let error = |message: String| Error::Git {
+        path: dir.to_path_buf(),
+        message,
+    };
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| error(format!("cannot run git: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(error(format!(
+            "git {} failed: {}",
+            args[0].as_ref().to_string_lossy(),
+            // The first line says what went wrong, git may add its usage after it.
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+        )));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Runs git in `dir` and splits its NUL separated output into paths.
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<PathBuf>> {
+    run(dir, args)?
+        .split(|&byte| byte == 0)
+        .filter(|path| !path.is_empty())
+        .map(|bytes| path_from_bytes(dir, bytes))
+        .collect()
+}
+
+/// Git prints paths as raw bytes, which are not UTF-8 for every file name.
+#[cfg(unix)]
+fn path_from_bytes(_dir: &Path, bytes: &[u8]) -> Result<PathBuf> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Ok(PathBuf::from(std::ffi::OsStr::from_bytes(bytes)))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(dir: &Path, bytes: &[u8]) -> Result<PathBuf> {
+    String::from_utf8(bytes.to_vec())
+        .map(PathBuf::from)
+        .map_err(|_| Error::InvalidUtf8 {
+            path: dir.join(String::from_utf8_lossy(bytes).as_ref()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_staged() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("build.rs"), "fn main() {}\n").unwrap();
+
+        Command::new("git")
+            .arg("init")
+            .arg("-q")
+            .arg(dir)
+            .status()
+            .unwrap();
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["add", "src/main.rs"])
+            .status()
+            .unwrap();
+
+        assert_eq!(
+            staged(dir).unwrap(),
+            vec![PathBuf::from("src/main.rs")],
+            "Only the staged file should be reported"
+        );
+        assert_eq!(
+            staged(&dir.join("src")).unwrap(),
+            vec![PathBuf::from("main.rs")]
+        );
+        assert!(matches!(
+            changed_since(dir, "no-such-ref"),
+            Err(Error::Git { path, .. }) if path == dir
+        ));
+    }
+
+    #[test]
+    fn test_last_commit_year() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let file = dir.join("src/main.rs");
+        fs::write(&file, "fn main() {}\n").unwrap();
+        let other = dir.join("build.rs");
+        fs::write(&other, "fn main() {}\n").unwrap();
+
+        let git = |date: &str, args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .env("GIT_COMMITTER_DATE", date)
+                .env("GIT_AUTHOR_DATE", date)
+                .status()
+                .unwrap()
+        };
+        git("2020-06-01T12:00:00Z", &["init", "-q"]);
+        assert_eq!(
+            CommitYears::default().last_commit_year(&file),
+            None,
+            "Untracked files have no commit"
+        );
+
+        git("2020-06-01T12:00:00Z", &["add", "."]);
+        git("2020-06-01T12:00:00Z", &["commit", "-q", "-m", "Add main"]);
+        fs::write(&other, "fn main() { edited() }\n").unwrap();
+        git(
+            "2022-06-01T12:00:00Z",
+            &["commit", "-q", "-a", "-m", "Edit build"],
+        );
+        let years = CommitYears::default();
+        years.load(&[dir.join("src")]);
+        assert_eq!(
+            years.years.read().unwrap().len(),
+            1,
+            "Only the history of the loaded paths should be read"
+        );
+        assert_eq!(years.last_commit_year(&file), Some(2020));
+        assert_eq!(
+            years.last_commit_year(&other),
+            Some(2022),
+            "Files outside the loaded paths should be read on their own"
+        );
+
+        fs::write(&file, "fn main() { edited() }\n").unwrap();
+        assert_eq!(
+            CommitYears::default().last_commit_year(&file),
+            None,
+            "Uncommitted changes are newer"
+        );
+        let years = CommitYears::default();
+        years.load(&[dir.to_path_buf()]);
+        assert_eq!(years.last_commit_year(&file), None);
+        assert_eq!(years.last_commit_year(&other), Some(2022));
+    }
+}
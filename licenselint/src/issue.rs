@@ -1,11 +1,152 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IssueKind {
+    /// The file has no license header at all.
+    MissingHeader,
+    /// The header is fine except for a copyright holder that is not allowed.
+    WrongAuthor,
+    /// The header is fine except for the copyright year.
+    WrongYear,
+    /// The header is for another license.
+    WrongLicense,
+    /// The header looks like a license header but does not match the expected text.
+    MalformedHeader,
+    /// A second license header follows the expected one.
+    DuplicateHeader,
+}
+
+impl IssueKind {
+    pub const ALL: [IssueKind; 6] = [
+        IssueKind::MissingHeader,
+        IssueKind::WrongAuthor,
+        IssueKind::WrongYear,
+        IssueKind::WrongLicense,
+        IssueKind::MalformedHeader,
+        IssueKind::DuplicateHeader,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueKind::MissingHeader => "missing-header",
+            IssueKind::WrongAuthor => "wrong-author",
+            IssueKind::WrongYear => "wrong-year",
+            IssueKind::WrongLicense => "wrong-license",
+            IssueKind::MalformedHeader => "malformed-header",
+            IssueKind::DuplicateHeader => "duplicate-header",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            IssueKind::MissingHeader => "The file has no license header.",
+            IssueKind::WrongAuthor => "The copyright holder is not an allowed author.",
+            IssueKind::WrongYear => "The copyright year is invalid.",
+            IssueKind::WrongLicense => "The license header is for another license.",
+            IssueKind::MalformedHeader => "The license header does not match the expected text.",
+            IssueKind::DuplicateHeader => "The file has more than one license header.",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            IssueKind::DuplicateHeader => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A 1-based, end-exclusive range of lines and columns, columns counted in characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    /// The whole `line` whose text is `text`.
+    pub fn line(line: usize, text: &str) -> Self {
+        Span {
+            start_line: line,
+            start_column: 1,
+            end_line: line,
+            end_column: text.chars().count() + 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Issue {
-    pub filename: String,
+    pub filename: PathBuf,
+    pub kind: IssueKind,
+    pub severity: Severity,
+    pub span: Span,
+    pub message: String,
+    pub expected: Option<String>,
+    pub found: Option<String>,
 }
 
 impl Issue {
-    pub fn new(filename: &str) -> Self {
+    pub fn new(filename: &Path, kind: IssueKind, span: Span, message: String) -> Self {
         Issue {
-            filename: filename.to_string(),
+            filename: filename.to_path_buf(),
+            kind,
+            severity: kind.severity(),
+            span,
+            message,
+            expected: None,
+            found: None,
         }
     }
+
+    pub fn with_expected_found(mut self, expected: &str, found: &str) -> Self {
+        self.expected = Some(expected.to_string());
+        self.found = Some(found.to_string());
+        self
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}: {} [{}]",
+            self.filename.display(),
+            self.span.start_line,
+            self.span.start_column,
+            self.severity,
+            self.message,
+            self.kind
+        )
+    }
 }
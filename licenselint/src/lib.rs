@@ -1,5 +1,10 @@
 pub mod config;
+pub mod error;
+pub mod git;
 pub mod issue;
 pub mod license;
 pub mod linter;
+pub mod report;
 pub mod template;
+
+pub use error::{Error, Result};
@@ -1,24 +1,134 @@
+use crate::error::Error;
 use std::str::FromStr;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum License {
     Apache20,
+    Mit,
 }
 
 impl FromStr for License {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(license: &str) -> Result<Self, Self::Err> {
         match license {
             "Apache-2.0" => Ok(License::Apache20),
-            _ => Err(()),
+            "MIT" => Ok(License::Mit),
+            _ => Err(Error::UnknownLicense {
+                name: license.to_string(),
+                suggestions: License::suggestions(license),
+            }),
         }
     }
 }
 
 impl License {
-    pub fn to_str(&self) -> &str {
+    pub const ALL: [License; 2] = [License::Apache20, License::Mit];
+
+    const APACHE20_TEXT: &'static str = r#"Copyright {year} {author}
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License."#;
+
+    const MIT_TEXT: &'static str = r#"Copyright (c) {year} {author}
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE."#;
+
+    /// The supported license names a misspelled `name` likely stands for, like `MIT` for `mit`.
+    fn suggestions(name: &str) -> Vec<&'static str> {
+        let name = name.to_lowercase();
+
+        License::ALL
+            .iter()
+            .map(|license| license.to_str())
+            .filter(|candidate| {
+                let candidate = candidate.to_lowercase();
+                candidate.starts_with(&name)
+                    || name.starts_with(&candidate)
+                    || edit_distance(&candidate, &name) <= 2
+            })
+            .collect()
+    }
+
+    pub fn to_str(&self) -> &'static str {
         match self {
             License::Apache20 => "Apache-2.0",
+            License::Mit => "MIT",
+        }
+    }
+
+    /// The license notice as plain text, with `{year}` and `{author}` placeholders.
+    pub fn text(&self) -> &'static str {
+        match self {
+            License::Apache20 => Self::APACHE20_TEXT,
+            License::Mit => Self::MIT_TEXT,
+        }
+    }
+}
+
+/// The Levenshtein distance between `a` and `b`, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
         }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_license_suggestions() {
+        let error = License::from_str("apache").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unsupported license 'apache', did you mean 'Apache-2.0'?"
+        );
+
+        let error = License::from_str("mit").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unsupported license 'mit', did you mean 'MIT'?"
+        );
+
+        let error = License::from_str("GPL-3.0").unwrap_err();
+        assert_eq!(error.to_string(), "unsupported license 'GPL-3.0'");
     }
 }
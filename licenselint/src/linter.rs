@@ -1,242 +1,939 @@
 use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::git::CommitYears;
 use crate::issue::Issue;
-use crate::license::License;
-use crate::template::arkts_apache20::ArktsApache20Template;
-use crate::template::clang_format_apache20::ClangFormatApache20Template;
-use crate::template::cmake_apache20::CmakeApache20Template;
-use crate::template::cmake_lists_apache20::CmakeListsApache20Template;
-use crate::template::cpp_apache20::CppApache20Template;
-use crate::template::go_apache20::GoApache20Template;
-use crate::template::hpp_apache20::HppApache20Template;
-use crate::template::in_apache20::InApache20Template;
-use crate::template::ipp_apache20::IppApache20Template;
-use crate::template::java_apache20::JavaApache20Template;
-use crate::template::properties_apache20::PropertiesApache20Template;
-use crate::template::python_apache20::PythonApache20Template;
-use crate::template::rust_apache20::RustApache20Template;
-use crate::template::toml_apache20::TomlApache20Template;
-use crate::template::tpp_apache20::TppApache20Template;
-use crate::template::typescript_apache20::TypeScriptApache20Template;
-use crate::template::xml_apache20::XmlApache20Template;
-use crate::template::yaml_apache20::YamlApache20Template;
+use crate::report::{CheckReport, FormatChange, FormatReport, FormattedReport};
+use crate::template::comment_style::CommentStyle;
+use crate::template::license_template::LicenseTemplate;
 use crate::template::LintTemplate;
-use ignore::WalkBuilder;
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+use ignore::{WalkBuilder, WalkState};
 use std::collections::HashMap;
-use std::path::Path;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::{fs, io};
 
 pub struct Linter<'a> {
     config: &'a Config,
-    templates: HashMap<String, Box<dyn LintTemplate>>,
-    exact_match_templates: HashMap<String, Box<dyn LintTemplate>>,
+    /// The effective config of every override in `config`, in declaration order.
+    overrides: Vec<(GlobMatcher, Config)>,
+    include: GlobSet,
+    exclude: GlobSet,
+    templates: HashMap<OsString, Box<dyn LintTemplate>>,
+    exact_match_templates: HashMap<OsString, Box<dyn LintTemplate>>,
+    /// Whether files passed explicitly are skipped when ignored, like files in a walked directory.
+    respect_ignore: bool,
+    /// The last commit years shared by the built-in templates.
+    commit_years: Arc<CommitYears>,
 }
 
 impl<'a> Linter<'a> {
-    pub fn new(config: &'a Config) -> Self {
+    /// Fails when a glob of `config` is invalid or a template cannot be built for it.
+    pub fn new(config: &'a Config) -> Result<Self> {
+        let overrides = config
+            .overrides
+            .iter()
+            .map(|over| {
+                let matcher = Glob::new(&over.path)
+                    .map_err(|e| Error::invalid_config(e.to_string()))?
+                    .compile_matcher();
+                Ok((matcher, config.with_override(over)))
+            })
+            .collect::<Result<_>>()?;
+
         let mut linter = Linter {
             config,
+            overrides,
+            include: build_glob_set(&config.include)?,
+            exclude: build_glob_set(&config.exclude_globs())?,
             templates: HashMap::new(),
             exact_match_templates: HashMap::new(),
+            respect_ignore: false,
+            commit_years: Arc::default(),
         };
 
-        linter.init_templates_by_license();
-
-        linter
-    }
-
-    fn init_templates_by_license(&mut self) {
-        match self.config.license {
-            License::Apache20 => {
-                self.add_exact_template(".clang-format", ClangFormatApache20Template {});
-                self.add_exact_template("CMakeLists.txt", CmakeListsApache20Template {});
-
-                self.add_template("ets", ArktsApache20Template {});
-                self.add_template("cmake", CmakeApache20Template {});
-                self.add_template("cpp", CppApache20Template {});
-                self.add_template("go", GoApache20Template {});
-                self.add_template("hpp", HppApache20Template {});
-                self.add_template("in", InApache20Template {});
-                self.add_template("ipp", IppApache20Template {});
-                self.add_template("java", JavaApache20Template {});
-                self.add_template("properties", PropertiesApache20Template {});
-                self.add_template("py", PythonApache20Template {});
-                self.add_template("rs", RustApache20Template {});
-                self.add_template("toml", TomlApache20Template {});
-                self.add_template("tpp", TppApache20Template {});
-                self.add_template("ts", TypeScriptApache20Template {});
-                self.add_template("xml", XmlApache20Template {});
-                self.add_template("yaml", YamlApache20Template {});
-                self.add_template("yml", YamlApache20Template {});
-            }
+        linter.init_templates()?;
+
+        Ok(linter)
+    }
+
+    fn init_templates(&mut self) -> Result<()> {
+        let exact_styles = [
+            (".clang-format", "clang-format", CommentStyle::HASH),
+            ("CMakeLists.txt", "cmake", CommentStyle::HASH),
+        ];
+
+        let styles = [
+            ("cmake", "cmake", CommentStyle::HASH),
+            ("cpp", "cpp", CommentStyle::DOUBLE_SLASH),
+            ("ets", "arkts", CommentStyle::DOUBLE_SLASH.with_shebang()),
+            ("go", "go", CommentStyle::DOUBLE_SLASH),
+            ("hpp", "cpp", CommentStyle::DOUBLE_SLASH),
+            ("in", "in", CommentStyle::HASH),
+            ("ipp", "cpp", CommentStyle::DOUBLE_SLASH),
+            ("java", "java", CommentStyle::SLASH_STAR),
+            ("properties", "properties", CommentStyle::HASH),
+            (
+                "py",
+                "python",
+                CommentStyle::HASH.with_shebang().with_coding_cookie(),
+            ),
+            ("rs", "rust", CommentStyle::DOUBLE_SLASH),
+            ("toml", "toml", CommentStyle::HASH),
+            ("tpp", "cpp", CommentStyle::DOUBLE_SLASH),
+            (
+                "ts",
+                "typescript",
+                CommentStyle::DOUBLE_SLASH.with_shebang(),
+            ),
+            ("xml", "xml", CommentStyle::XML),
+            ("yaml", "yaml", CommentStyle::HASH),
+            ("yml", "yaml", CommentStyle::HASH),
+        ];
+
+        let commit_years = self.commit_years.clone();
+        let template = |language, style| {
+            LicenseTemplate::new(language, style).with_commit_years(commit_years.clone())
+        };
+
+        for (filename, language, style) in exact_styles {
+            self.add_exact_template(filename, template(language, style))?;
+        }
+
+        for (extension, language, style) in styles {
+            self.add_template(extension, template(language, style))?;
         }
+
+        Ok(())
     }
 
     /// Add a template for a specific file extension.
-    pub fn add_template<T: LintTemplate + 'static>(&mut self, extension: &str, template: T) {
+    pub fn add_template<T: LintTemplate + 'static>(
+        &mut self,
+        extension: &str,
+        template: T,
+    ) -> Result<()> {
+        self.prepare(&template)?;
         self.templates
-            .insert(extension.to_string(), Box::new(template));
+            .insert(OsString::from(extension), Box::new(template));
+        Ok(())
     }
 
     /// Add an exact match template for specific filenames (like .clang-format).
-    pub fn add_exact_template<T: LintTemplate + 'static>(&mut self, filename: &str, template: T) {
+    pub fn add_exact_template<T: LintTemplate + 'static>(
+        &mut self,
+        filename: &str,
+        template: T,
+    ) -> Result<()> {
+        self.prepare(&template)?;
         self.exact_match_templates
-            .insert(filename.to_string(), Box::new(template));
+            .insert(OsString::from(filename), Box::new(template));
+        Ok(())
     }
 
-    pub fn check_files_in_dir(
-        &self,
-        dir: &Path,
-    ) -> Result<Vec<Issue>, Vec<(std::path::PathBuf, io::Error)>> {
-        let mut all_issues = Vec::new();
+    /// Prepares `template` for the config and every override, so no file pays for it.
+    fn prepare(&self, template: &dyn LintTemplate) -> Result<()> {
+        template.prepare(self.config)?;
+
+        for (_, config) in &self.overrides {
+            template.prepare(config)?;
+        }
+
+        Ok(())
+    }
+
+    /// Skips files passed explicitly to the `*_paths` methods when they are ignored by
+    /// `.gitignore`, `.licenselintignore` or the `include` and `exclude` globs. They are linted
+    /// anyway by default.
+    pub fn set_respect_ignore(&mut self, respect_ignore: bool) {
+        self.respect_ignore = respect_ignore;
+    }
+
+    pub fn check_files_in_dir(&self, dir: &Path) -> CheckReport {
+        self.check_paths(dir, &[dir.to_path_buf()])
+    }
 
-        let result = self.travel_dir(dir, |path| match fs::read_to_string(path) {
-            Ok(content) => {
-                let issues = self.check(path.to_str().unwrap(), &content);
-                all_issues.extend(issues);
+    /// Checks the given files and directories, relative to `root`. Directories are walked with the
+    /// ignore rules, and override and include globs are matched against paths relative to `root`.
+    /// Files that cannot be checked are reported in `errors` without stopping the others.
+    pub fn check_paths(&self, root: &Path, paths: &[PathBuf]) -> CheckReport {
+        let report = Mutex::new(CheckReport::default());
+
+        let errors = self.travel(root, paths, |path, relative_path| {
+            let template = match self.template_for(path) {
+                Some(template) => template,
+                None => {
+                    report.lock().unwrap().files_skipped += 1;
+                    return Ok(());
+                }
+            };
+
+            let content = match read_text(path, self.config.max_file_size, Some(CHECK_READ_LIMIT)) {
+                Ok(Some(content)) => content,
+                Ok(None) | Err(Error::InvalidUtf8 { .. }) => {
+                    report.lock().unwrap().files_skipped += 1;
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            };
+            let config = self.config_for(relative_path);
+            let issues = template.check(config, path, &content);
+
+            let mut report = report.lock().unwrap();
+            report.files_scanned += 1;
+            *report
+                .languages
+                .entry(template.language().to_string())
+                .or_default() += 1;
+            report.issues.extend(issues);
+
+            Ok(())
+        });
+
+        let mut report = report.into_inner().unwrap();
+        // Files are checked in parallel, the issues of each file stay in order.
+        report.issues.sort_by(|a, b| a.filename.cmp(&b.filename));
+        report.errors = errors;
+
+        report
+    }
+
+    pub fn format_files_in_dir(&self, dir: &Path) -> FormattedReport {
+        self.format_paths(dir, &[dir.to_path_buf()])
+    }
+
+    /// Formats the given files and directories, relative to `root`, like `check_paths` checks them.
+    /// Every file is written as soon as it is formatted.
+    pub fn format_paths(&self, root: &Path, paths: &[PathBuf]) -> FormattedReport {
+        let formatted = Mutex::new(Vec::new());
+
+        let errors =
+            self.format_travel(root, paths, |path, relative_path, _, formatted_content| {
+                fs::write(path, formatted_content).map_err(|e| Error::io(path, e))?;
+                formatted.lock().unwrap().push(relative_path.to_path_buf());
                 Ok(())
-            }
-            Err(e) => Err(e),
+            });
+
+        let mut formatted = formatted.into_inner().unwrap();
+        formatted.sort();
+
+        FormattedReport { formatted, errors }
+    }
+
+    /// The files `format_files_in_dir` would rewrite, sorted by path, without touching disk.
+    pub fn format_changes_in_dir(&self, dir: &Path) -> FormatReport {
+        self.format_changes_in_paths(dir, &[dir.to_path_buf()])
+    }
+
+    /// The files `format_paths` would rewrite, sorted by path, without touching disk.
+    pub fn format_changes_in_paths(&self, root: &Path, paths: &[PathBuf]) -> FormatReport {
+        let changes = Mutex::new(Vec::new());
+
+        let errors = self.format_travel(root, paths, |path, relative_path, original, formatted| {
+            changes.lock().unwrap().push(FormatChange {
+                path: path.to_path_buf(),
+                relative_path: relative_path.to_path_buf(),
+                original,
+                formatted,
+            });
+            Ok(())
         });
 
-        result.map(|_| all_issues)
+        let mut changes = changes.into_inner().unwrap();
+        changes.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+        FormatReport { changes, errors }
     }
 
-    pub fn format_files_in_dir(
-        &self,
-        dir: &Path,
-    ) -> Result<(), Vec<(std::path::PathBuf, io::Error)>> {
-        self.travel_dir(dir, |path| match fs::read_to_string(path) {
-            Ok(content) => {
-                let formatted_content = self.format(path.to_str().unwrap(), &content);
-                if formatted_content != content {
-                    fs::write(path, formatted_content)
-                } else {
-                    Ok(())
-                }
+    /// Formats the files under `paths`, relative to `root`, and calls `on_change` from several
+    /// threads with the path, the relative path, the content and the formatted content of every
+    /// file that formatting changes. Returns the errors sorted by path.
+    fn format_travel<F>(&self, root: &Path, paths: &[PathBuf], on_change: F) -> Vec<Error>
+    where
+        F: Fn(&Path, &Path, String, String) -> Result<()> + Sync,
+    {
+        self.travel(root, paths, |path, relative_path| {
+            if self.template_for(path).is_none() {
+                return Ok(());
+            }
+
+            let content = match read_text(path, self.config.max_file_size, None) {
+                Ok(Some(content)) => content,
+                Ok(None) | Err(Error::InvalidUtf8 { .. }) => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            let config = self.config_for(relative_path);
+            let formatted_content = self.format_with_config(config, path, &content);
+
+            if formatted_content == content {
+                return Ok(());
             }
-            Err(e) => Err(e),
+
+            on_change(path, relative_path, content, formatted_content)
         })
     }
 
-    fn travel_dir<F>(
-        &self,
-        dir: &Path,
-        mut file_handler: F,
-    ) -> Result<(), Vec<(std::path::PathBuf, io::Error)>>
+    /// Walks `paths`, relative to `root`, and calls `file_handler` from several threads with the
+    /// path of every file and its path relative to `root`. Returns the errors sorted by path.
+    fn travel<F>(&self, root: &Path, paths: &[PathBuf], file_handler: F) -> Vec<Error>
     where
-        F: FnMut(&Path) -> Result<(), io::Error>,
+        F: Fn(&Path, &Path) -> Result<()> + Sync,
     {
-        let mut errors = Vec::new();
-        let mut builder = WalkBuilder::new(dir);
-
-        builder
-            .ignore(false)
-            .hidden(false)
-            .follow_links(true)
-            .parents(true)
-            .require_git(false)
-            .git_exclude(true)
-            .git_global(true)
-            .git_ignore(true);
-
-        builder.add_ignore(".licenselintignore");
-
-        let walker = builder.build();
-
-        for entry in walker {
-            match entry {
-                Ok(entry) => {
-                    if entry
-                        .path()
-                        .components()
-                        .any(|comp| comp.as_os_str() == ".git")
-                    {
-                        continue;
-                    }
-
-                    if entry.path().file_name() == Some(std::ffi::OsStr::new(".gitmodules")) {
-                        continue;
-                    }
-
-                    let ignored_extensions = ["md", "png", "xlsx", "xlss"];
+        let cover_last_modified = std::iter::once(self.config)
+            .chain(self.overrides.iter().map(|(_, config)| config))
+            .any(|config| config.year_policy.cover_last_modified);
+        if cover_last_modified {
+            // One git run for all the paths instead of one per file. Loading ahead also keeps
+            // files formatted before their lookup from looking modified since their last commit.
+            let paths: Vec<PathBuf> = paths.iter().map(|path| root.join(path)).collect();
+            self.commit_years.load(&paths);
+        }
 
-                    if entry
-                        .path()
-                        .extension()
-                        .and_then(|ext| ext.to_str())
-                        .map_or(false, |ext| ignored_extensions.contains(&ext))
-                    {
-                        continue;
-                    }
+        let travel = Travel {
+            root,
+            include: &self.include,
+            exclude: &self.exclude,
+            has_include: !self.config.include.is_empty(),
+            errors: Mutex::new(Vec::new()),
+        };
 
-                    if let Ok(content) = std::fs::read(entry.path()) {
-                        if std::str::from_utf8(&content).is_err() {
-                            continue;
-                        }
-                    } else {
-                        continue;
-                    }
+        // Sorted paths list the contents of a directory right after it, so an argument inside
+        // another one, which would be linted twice, follows the argument it is in.
+        let mut paths: Vec<PathBuf> = paths.iter().map(|path| root.join(path)).collect();
+        paths.sort();
+        let mut covering: Option<PathBuf> = None;
+        paths.retain(|path| {
+            if covering
+                .as_ref()
+                .is_some_and(|covering| path.starts_with(covering))
+            {
+                return false;
+            }
+            covering = Some(path.clone());
+            true
+        });
 
-                    if entry.file_type().map_or(false, |ft| ft.is_file()) {
-                        if let Err(e) = file_handler(entry.path()) {
-                            errors.push((entry.path().to_path_buf(), e));
+        for path in paths {
+            let walker = if path.is_dir() {
+                walk_builder(&path, root, &self.exclude, None).build_parallel()
+            } else if !path.exists() {
+                travel.error(Error::io(
+                    &path,
+                    io::Error::new(io::ErrorKind::NotFound, "no such file or directory"),
+                ));
+                continue;
+            } else if self.respect_ignore {
+                // Walk down to the file only, so the ignore files of every directory above it
+                // apply.
+                let walk_root = match path.parent() {
+                    Some(parent) if !path.starts_with(root) => parent,
+                    _ => root,
+                };
+                walk_builder(walk_root, root, &self.exclude, Some(path.clone())).build_parallel()
+            } else {
+                travel.visit(&path, false, &file_handler);
+                continue;
+            };
+
+            walker.run(|| {
+                Box::new(|entry| {
+                    match entry {
+                        Ok(entry) => {
+                            if entry.file_type().is_some_and(|ft| ft.is_file()) {
+                                travel.visit(entry.path(), true, &file_handler);
+                            }
                         }
+                        Err(e) => travel.error(walk_error(&path, e)),
                     }
-                }
-                Err(e) => errors.push((dir.to_path_buf(), io::Error::new(io::ErrorKind::Other, e))),
-            }
+                    WalkState::Continue
+                })
+            });
         }
 
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
+        let mut errors = travel.errors.into_inner().unwrap();
+        errors.sort_by(|a, b| a.path().cmp(&b.path()));
+
+        errors
+    }
+
+    /// The config for a file, given its path relative to the linted directory.
+    pub fn config_for(&self, relative_path: &Path) -> &Config {
+        self.overrides
+            .iter()
+            .rev()
+            .find(|(matcher, _)| matcher.is_match(relative_path))
+            .map_or(self.config, |(_, config)| config)
+    }
+
+    pub fn check(&self, path: &Path, content: &str) -> Vec<Issue> {
+        self.check_with_config(self.config_for(path), path, content)
+    }
+
+    /// Checks `content` as the file at `relative_path` in `root`, which need not exist. Overrides
+    /// are matched against `relative_path`, while file dates are looked up in `root`.
+    pub fn check_content(&self, root: &Path, relative_path: &Path, content: &str) -> Vec<Issue> {
+        let config = self.config_for(relative_path);
+        self.check_with_config(config, &root.join(relative_path), content)
+    }
+
+    fn check_with_config(&self, config: &Config, path: &Path, content: &str) -> Vec<Issue> {
+        match self.template_for(path) {
+            Some(template) => template.check(config, path, content),
+            None => Vec::new(),
         }
     }
 
-    pub fn check(&self, filename: &str, content: &str) -> Vec<Issue> {
-        let mut all_issues = Vec::new();
+    pub fn format(&self, path: &Path, content: &str) -> String {
+        self.format_with_config(self.config_for(path), path, content)
+    }
 
-        let path = Path::new(filename);
+    /// Formats `content` as the file at `relative_path` in `root`, like `check_content`.
+    pub fn format_content(&self, root: &Path, relative_path: &Path, content: &str) -> String {
+        let config = self.config_for(relative_path);
+        self.format_with_config(config, &root.join(relative_path), content)
+    }
 
-        // Check for exact match templates
-        if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-            if let Some(template) = self.exact_match_templates.get(file_name) {
-                let issues = template.check(self.config, filename, content);
-                all_issues.extend(issues);
-            }
+    fn format_with_config(&self, config: &Config, path: &Path, content: &str) -> String {
+        match self.template_for(path) {
+            Some(template) => template.format(config, path, content),
+            None => content.to_string(),
         }
+    }
 
-        // Check for extension-based templates if no exact match
-        if all_issues.is_empty() {
-            if let Some(extension) = path.extension().and_then(|s| s.to_str()) {
-                if let Some(template) = self.templates.get(extension) {
-                    let issues = template.check(self.config, filename, content);
-                    all_issues.extend(issues);
-                }
-            }
+    /// Finds the template for a file, exact filename matches first, then by extension.
+    pub fn template_for(&self, path: &Path) -> Option<&dyn LintTemplate> {
+        let exact_template = path
+            .file_name()
+            .and_then(|file_name| self.exact_match_templates.get(file_name));
+
+        match exact_template {
+            Some(template) => Some(template.as_ref()),
+            None => self.extension_template(path),
         }
+    }
+
+    /// Finds the template for the file extension, following the extension mappings in `Config`.
+    fn extension_template(&self, path: &Path) -> Option<&dyn LintTemplate> {
+        let extension = path.extension()?;
+        let extension = extension
+            .to_str()
+            .and_then(|extension| self.config.extensions.get(extension))
+            .map_or(extension, |mapped| OsStr::new(mapped));
 
-        all_issues
+        self.templates
+            .get(extension)
+            .map(|template| template.as_ref())
     }
+}
 
-    pub fn format(&self, filename: &str, content: &str) -> String {
-        let path = Path::new(filename);
+/// Turns an error walking `dir` into an error about the path it is about.
+fn walk_error(dir: &Path, error: ignore::Error) -> Error {
+    match error {
+        ignore::Error::WithPath { path, err } => match *err {
+            ignore::Error::Io(e) => Error::io(&path, e),
+            err => Error::Walk {
+                path: Some(path),
+                source: err,
+            },
+        },
+        ignore::Error::WithDepth { err, .. } => walk_error(dir, *err),
+        ignore::Error::Io(e) => Error::io(dir, e),
+        error => Error::Walk {
+            path: Some(dir.to_path_buf()),
+            source: error,
+        },
+    }
+}
 
-        // Check for exact match templates
-        if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-            if let Some(template) = self.exact_match_templates.get(file_name) {
-                return template.format(self.config, filename, content);
+/// How much of a file `check` reads, enough for any license header and the comment after it.
+const CHECK_READ_LIMIT: usize = 64 * 1024;
+
+/// Reads the first `limit` bytes of `path`, or all of it, as text. Returns `None` for files larger
+/// than `max_file_size` bytes.
+fn read_text(path: &Path, max_file_size: u64, limit: Option<usize>) -> Result<Option<String>> {
+    read_bytes(path, max_file_size, limit)
+        .map_err(|e| Error::io(path, e))?
+        .map(|(content, truncated)| match String::from_utf8(content) {
+            Ok(content) => Ok(content),
+            // The limit may split the last character, drop it.
+            Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+                let valid_up_to = e.utf8_error().valid_up_to();
+                let mut content = e.into_bytes();
+                content.truncate(valid_up_to);
+                String::from_utf8(content).map_err(|_| Error::InvalidUtf8 {
+                    path: path.to_path_buf(),
+                })
             }
+            Err(_) => Err(Error::InvalidUtf8 {
+                path: path.to_path_buf(),
+            }),
+        })
+        .transpose()
+}
+
+/// The first `limit` bytes of `path`, or all of it, and whether the content was cut.
+fn read_bytes(
+    path: &Path,
+    max_file_size: u64,
+    limit: Option<usize>,
+) -> io::Result<Option<(Vec<u8>, bool)>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    if len > max_file_size {
+        return Ok(None);
+    }
+
+    let mut content = Vec::new();
+    let truncated = match limit {
+        Some(limit) if len > limit as u64 => {
+            file.take(limit as u64).read_to_end(&mut content)?;
+            true
+        }
+        _ => {
+            file.read_to_end(&mut content)?;
+            false
+        }
+    };
+
+    Ok(Some((content, truncated)))
+}
+
+/// A walker of `dir` honoring `.gitignore` and `.licenselintignore` files, which skips the
+/// directories matched by `exclude` relative to `root`. With a `target`, only the directories
+/// leading to it are walked.
+fn walk_builder(
+    dir: &Path,
+    root: &Path,
+    exclude: &GlobSet,
+    target: Option<PathBuf>,
+) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(dir);
+
+    builder
+        .ignore(false)
+        .hidden(false)
+        .follow_links(true)
+        .parents(true)
+        .require_git(false)
+        .git_exclude(true)
+        .git_global(true)
+        .git_ignore(true)
+        .add_custom_ignore_filename(".licenselintignore");
+
+    let root = root.to_path_buf();
+    let exclude = exclude.clone();
+
+    builder.filter_entry(move |entry| {
+        if target
+            .as_ref()
+            .is_some_and(|target| !target.starts_with(entry.path()))
+        {
+            return false;
+        }
+
+        let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+        let relative_path = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+
+        !(is_dir && entry.depth() > 0 && exclude.is_match(relative_path))
+    });
+
+    builder
+}
+
+/// The state of a `Linter::travel` walk.
+struct Travel<'a> {
+    root: &'a Path,
+    include: &'a GlobSet,
+    exclude: &'a GlobSet,
+    has_include: bool,
+    errors: Mutex<Vec<Error>>,
+}
+
+impl Travel<'_> {
+    /// Calls `file_handler` for `path` if it is a text file to lint. `include` and `exclude` are
+    /// only applied to `filtered` paths, the ones that were walked rather than passed explicitly.
+    fn visit<F>(&self, path: &Path, filtered: bool, file_handler: &F)
+    where
+        F: Fn(&Path, &Path) -> Result<()>,
+    {
+        if path.components().any(|comp| comp.as_os_str() == ".git") {
+            return;
+        }
+
+        if path.file_name() == Some(OsStr::new(".gitmodules")) {
+            return;
         }
 
-        // Check for extension-based templates
-        if let Some(extension) = path.extension().and_then(|s| s.to_str()) {
-            if let Some(template) = self.templates.get(extension) {
-                return template.format(self.config, filename, content);
+        let relative_path = path.strip_prefix(self.root).unwrap_or(path);
+
+        if filtered {
+            if self.has_include && !self.include.is_match(relative_path) {
+                return;
+            }
+
+            if self.exclude.is_match(relative_path) {
+                return;
             }
         }
 
-        content.to_string()
+        if let Err(e) = file_handler(path, relative_path) {
+            self.error(e);
+        }
+    }
+
+    fn error(&self, error: Error) {
+        self.errors.lock().unwrap().push(error);
+    }
+}
+
+fn build_glob_set(globs: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for glob in globs {
+        let glob = Glob::new(glob).map_err(|e| Error::invalid_config(e.to_string()))?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|e| Error::invalid_config(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{HeaderStyle, Override};
+    use crate::license::License;
+
+    #[test]
+    fn test_templates_by_filename() {
+        let config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        let linter = Linter::new(&config).unwrap();
+
+        let cases = [
+            ("src/main.ets", "// Copyright 1997 ZhangJian He"),
+            ("src/main.ts", "// Copyright 1997 ZhangJian He"),
+            ("src/Main.java", "/*"),
+            ("pom.xml", r#"<?xml version="1.0" encoding="UTF-8"?>"#),
+            ("CMakeLists.txt", "# Copyright 1997 ZhangJian He"),
+            (".clang-format", "# Copyright 1997 ZhangJian He"),
+        ];
+
+        for (filename, first_line) in cases {
+            let formatted_content = linter.format(Path::new(filename), "content\n");
+            assert_eq!(
+                formatted_content.lines().next(),
+                Some(first_line),
+                "Unexpected header for {}",
+                filename
+            );
+            assert!(
+                linter
+                    .check(Path::new(filename), &formatted_content)
+                    .is_empty(),
+                "Formatted {} should pass the check",
+                filename
+            );
+        }
+
+        assert_eq!(
+            linter.format(Path::new("notes.txt"), "content\n"),
+            "content\n"
+        );
+        assert!(linter.check(Path::new("notes.txt"), "content\n").is_empty());
+    }
+
+    #[test]
+    fn test_extension_mapping() {
+        let mut config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        config
+            .extensions
+            .insert("mjs".to_string(), "ts".to_string());
+        let linter = Linter::new(&config).unwrap();
+
+        let formatted_content = linter.format(Path::new("src/index.mjs"), "content\n");
+        assert!(formatted_content.starts_with("// Copyright 1997 ZhangJian He\n"));
+        assert_eq!(
+            linter.check(Path::new("src/index.mjs"), "content\n").len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_override_by_path() {
+        let mut config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        config.overrides.push(Override {
+            path: "third_party/**".to_string(),
+            license: Some(License::Mit),
+            header_style: Some(HeaderStyle::Spdx),
+            formatted_author: Some("Upstream Authors".to_string()),
+            allowed_authors: Vec::new(),
+        });
+        let linter = Linter::new(&config).unwrap();
+
+        let formatted_content = linter.format(Path::new("third_party/lib/lib.rs"), "content\n");
+        assert!(formatted_content.starts_with(
+            "// SPDX-FileCopyrightText: 1997 Upstream Authors\n// SPDX-License-Identifier: MIT\n"
+        ));
+        assert!(linter
+            .check(Path::new("third_party/lib/lib.rs"), &formatted_content)
+            .is_empty());
+        assert_eq!(
+            linter
+                .check(Path::new("src/lib.rs"), &formatted_content)
+                .len(),
+            1
+        );
+
+        let formatted_content = linter.format(Path::new("src/lib.rs"), "content\n");
+        assert!(formatted_content.starts_with("// Copyright 1997 ZhangJian He\n"));
+    }
+
+    #[test]
+    fn test_check_files_in_dir_report() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("lib.rs"), "// Copyright 1997 ZhangJian He\n").unwrap();
+        fs::write(dir.join("build.py"), "print(1)\n").unwrap();
+        fs::write(dir.join("notes.txt"), "notes\n").unwrap();
+        fs::write(dir.join("image.bin"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        let linter = Linter::new(&config).unwrap();
+        let report = linter.check_files_in_dir(dir);
+
+        assert_eq!(report.files_scanned, 3);
+        assert_eq!(report.files_skipped, 2);
+        assert_eq!(report.languages.get("rust"), Some(&2));
+        assert_eq!(report.languages.get("python"), Some(&1));
+        assert_eq!(report.issues.len(), 3);
+        assert!(
+            report
+                .issues
+                .windows(2)
+                .all(|pair| pair[0].filename <= pair[1].filename),
+            "Issues should be sorted by file"
+        );
+    }
+
+    #[test]
+    fn test_format_changes_in_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("notes.txt"), "notes\n").unwrap();
+
+        let config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        let linter = Linter::new(&config).unwrap();
+        let changes = linter.format_changes_in_dir(dir).changes;
+
+        assert_eq!(changes.len(), 1, "Only the Rust file should change");
+        assert_eq!(changes[0].relative_path, Path::new("main.rs"));
+        assert_eq!(changes[0].original, "fn main() {}\n");
+        assert!(changes[0].formatted.ends_with("\n\nfn main() {}\n"));
+        assert_eq!(
+            fs::read_to_string(dir.join("main.rs")).unwrap(),
+            "fn main() {}\n",
+            "Planning changes should not touch disk"
+        );
+
+        let report = linter.format_files_in_dir(dir);
+        assert!(report.errors.is_empty());
+        assert_eq!(report.formatted, vec![PathBuf::from("main.rs")]);
+        assert_eq!(
+            fs::read_to_string(dir.join("main.rs")).unwrap(),
+            changes[0].formatted
+        );
+        assert!(
+            linter.format_files_in_dir(dir).formatted.is_empty(),
+            "Formatted files should not be rewritten again"
+        );
+    }
+
+    #[test]
+    fn test_check_paths() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("gen")).unwrap();
+        fs::write(dir.join(".gitignore"), "gen/\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("src/lib.rs"), "fn lib() {}\n").unwrap();
+        fs::write(dir.join("gen/out.rs"), "fn out() {}\n").unwrap();
+
+        let config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        let mut linter = Linter::new(&config).unwrap();
+        let paths = vec![PathBuf::from("src/main.rs"), PathBuf::from("gen/out.rs")];
+
+        let report = linter.check_paths(dir, &paths);
+        assert_eq!(
+            report.files_scanned, 2,
+            "Files passed explicitly should be checked even if ignored"
+        );
+
+        linter.set_respect_ignore(true);
+        let report = linter.check_paths(dir, &paths);
+        assert_eq!(report.files_scanned, 1);
+        assert!(report.issues[0].filename.ends_with("main.rs"));
+
+        let paths = vec![
+            PathBuf::from("src"),
+            PathBuf::from("src/main.rs"),
+            PathBuf::from("src/"),
+        ];
+        let report = linter.check_paths(dir, &paths);
+        assert_eq!(
+            report.files_scanned, 2,
+            "Files passed within another argument should be checked once"
+        );
+        assert_eq!(report.issues.len(), 2);
+
+        let paths = vec![PathBuf::from("src/missing.rs"), PathBuf::from("src/lib.rs")];
+        let report = linter.check_paths(dir, &paths);
+        assert_eq!(report.errors.len(), 1);
+        assert!(
+            report.errors[0].path().unwrap().ends_with("src/missing.rs"),
+            "The error should be about the missing path"
+        );
+        assert_eq!(
+            report.issues.len(),
+            1,
+            "Files that can be read should still be checked"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_filename() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join(OsStr::from_bytes(b"caf\xe9.rs"));
+        fs::write(&path, "fn main() {}\n").unwrap();
+
+        let config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        let linter = Linter::new(&config).unwrap();
+
+        let report = linter.check_files_in_dir(dir);
+        assert_eq!(report.files_scanned, 1);
+        assert_eq!(
+            report.issues[0].filename, path,
+            "Files whose name is not UTF-8 should be linted"
+        );
+
+        let report = linter.format_files_in_dir(dir);
+        assert!(report.errors.is_empty());
+        assert!(linter
+            .check(&path, &fs::read_to_string(&path).unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_read_text() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = dir.join("main.rs");
+
+        fs::write(&path, "// é\n").unwrap();
+        assert_eq!(
+            read_text(&path, 1024, None).unwrap(),
+            Some("// é\n".to_string())
+        );
+        assert_eq!(
+            read_text(&path, 1024, Some(4)).unwrap(),
+            Some("// ".to_string()),
+            "A character split by the limit should be dropped"
+        );
+        assert_eq!(
+            read_text(&path, 4, None).unwrap(),
+            None,
+            "Files larger than the size cap should be skipped"
+        );
+
+        fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+        assert!(
+            matches!(read_text(&path, 1024, None), Err(Error::InvalidUtf8 { .. })),
+            "Binary files should be reported as not UTF-8"
+        );
+    }
+
+    #[test]
+    fn test_invalid_glob() {
+        let mut config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        config.exclude = vec!["src/[".to_string()];
+
+        assert!(
+            matches!(Linter::new(&config), Err(Error::InvalidConfig { .. })),
+            "An invalid exclude glob should be rejected up front"
+        );
+    }
+
+    #[test]
+    fn test_default_exclude() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("target/debug")).unwrap();
+        fs::create_dir_all(dir.join("web/node_modules/pkg")).unwrap();
+        fs::create_dir_all(dir.join("src/generated")).unwrap();
+        fs::write(dir.join("target/debug/build.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("web/node_modules/pkg/index.ts"), "export {}\n").unwrap();
+        fs::write(dir.join("web/pnpm-lock.yaml"), "lockfileVersion: 9\n").unwrap();
+        fs::write(dir.join("src/generated/out.rs"), "fn out() {}\n").unwrap();
+        fs::write(dir.join("src/.licenselintignore"), "generated/\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+        let mut config = Config::new_from_author(
+            License::Apache20,
+            "ZhangJian He".to_string(),
+            "1997".to_string(),
+        );
+        let report = Linter::new(&config).unwrap().check_files_in_dir(dir);
+        assert_eq!(
+            report.files_scanned, 1,
+            "Build output, dependencies, lockfiles and ignored files should not be linted"
+        );
+
+        config.default_exclude = false;
+        config.exclude = vec!["web".to_string()];
+        let report = Linter::new(&config).unwrap().check_files_in_dir(dir);
+        assert_eq!(report.files_scanned, 2);
     }
 }
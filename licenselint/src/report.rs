@@ -0,0 +1,47 @@
+use crate::error::Error;
+use crate::issue::Issue;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The outcome of checking a directory.
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub issues: Vec<Issue>,
+    /// Files that were checked against a template.
+    pub files_scanned: usize,
+    /// Files that were walked but not checked, because no template applies, they are binary or
+    /// they are larger than the size cap.
+    pub files_skipped: usize,
+    /// Number of scanned files per template language.
+    pub languages: BTreeMap<String, usize>,
+    /// Files that could not be checked, sorted by path. Their issues are missing from `issues`.
+    pub errors: Vec<Error>,
+}
+
+/// A file `format` would rewrite, with its content before and after.
+#[derive(Debug)]
+pub struct FormatChange {
+    pub path: PathBuf,
+    /// The path relative to the formatted directory.
+    pub relative_path: PathBuf,
+    pub original: String,
+    pub formatted: String,
+}
+
+/// The outcome of formatting files in place.
+#[derive(Debug, Default)]
+pub struct FormattedReport {
+    /// Files that were rewritten, relative to the formatted directory and sorted.
+    pub formatted: Vec<PathBuf>,
+    /// Files that could not be formatted, sorted by path.
+    pub errors: Vec<Error>,
+}
+
+/// The changes formatting a directory would make.
+#[derive(Debug, Default)]
+pub struct FormatReport {
+    /// Files that would be rewritten, sorted by path.
+    pub changes: Vec<FormatChange>,
+    /// Files that could not be formatted, sorted by path.
+    pub errors: Vec<Error>,
+}
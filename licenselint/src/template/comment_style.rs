@@ -0,0 +1,268 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// How a license header is wrapped in comments for a given language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommentSyntax {
+    /// Every header line starts with `prefix`, e.g. `// ` or `# `.
+    Line { prefix: &'static str },
+    /// The header is enclosed by `start` and `end` lines, and every line in between starts
+    /// with `middle`, e.g. `/*`, ` * ` and ` */`.
+    Block {
+        start: &'static str,
+        middle: &'static str,
+        end: &'static str,
+    },
+}
+
+/// A line that must come before the header, like the XML declaration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Prologue {
+    /// The line added to files that have none.
+    pub line: &'static str,
+    /// Anything starting with `start` and whitespace, up to the first `end`, is kept as the
+    /// prologue, e.g. an XML declaration with other attributes or spread over several lines.
+    pub start: &'static str,
+    pub end: &'static str,
+}
+
+impl Prologue {
+    /// Whether `content` starts with a prologue, even one missing its `end`.
+    pub fn starts(&self, content: &str) -> bool {
+        content
+            .strip_prefix(self.start)
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+    }
+
+    /// Splits the prologue off the start of `content`, returning it without the line break after
+    /// it and the content after that line break.
+    pub fn split<'c>(&self, content: &'c str) -> Option<(&'c str, &'c str)> {
+        if !self.starts(content) {
+            return None;
+        }
+
+        let end = content.find(self.end)? + self.end.len();
+        let (prologue, rest) = content.split_at(end);
+        let rest = rest.trim_start_matches([' ', '\t']);
+        let rest = rest
+            .strip_prefix("\r\n")
+            .or_else(|| rest.strip_prefix('\n'))
+            .unwrap_or(rest);
+
+        Some((prologue, rest))
+    }
+}
+
+/// The byte order mark some editors write at the start of UTF-8 files.
+pub const BOM: char = '\u{feff}';
+
+/// Splits the blank lines at the start of `content` off the rest of it.
+pub fn split_blank_lines(content: &str) -> (&str, &str) {
+    let end = content
+        .split_inclusive('\n')
+        .take_while(|line| line.trim().is_empty() && line.ends_with('\n'))
+        .map(|line| line.len())
+        .sum();
+
+    content.split_at(end)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommentStyle {
+    pub syntax: CommentSyntax,
+    pub prologue: Option<Prologue>,
+    /// Whether a `#!` interpreter line on the first line is kept above the header.
+    pub shebang: bool,
+    /// Whether a Python encoding cookie on the first two lines is kept above the header.
+    pub coding_cookie: bool,
+}
+
+impl CommentStyle {
+    pub const DOUBLE_SLASH: CommentStyle = CommentStyle::line("// ");
+    pub const HASH: CommentStyle = CommentStyle::line("# ");
+    pub const SLASH_STAR: CommentStyle = CommentStyle::block("/*", " * ", " */");
+    pub const XML: CommentStyle =
+        CommentStyle::block("<!--", "    ", "-->").with_prologue(Prologue {
+            line: r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            start: "<?xml",
+            end: "?>",
+        });
+
+    pub const fn line(prefix: &'static str) -> Self {
+        CommentStyle {
+            syntax: CommentSyntax::Line { prefix },
+            prologue: None,
+            shebang: false,
+            coding_cookie: false,
+        }
+    }
+
+    pub const fn block(start: &'static str, middle: &'static str, end: &'static str) -> Self {
+        CommentStyle {
+            syntax: CommentSyntax::Block { start, middle, end },
+            prologue: None,
+            shebang: false,
+            coding_cookie: false,
+        }
+    }
+
+    pub const fn with_prologue(mut self, prologue: Prologue) -> Self {
+        self.prologue = Some(prologue);
+        self
+    }
+
+    /// Keeps a shebang above the header, for languages run as scripts.
+    pub const fn with_shebang(mut self) -> Self {
+        self.shebang = true;
+        self
+    }
+
+    /// Keeps a Python encoding cookie above the header.
+    pub const fn with_coding_cookie(mut self) -> Self {
+        self.coding_cookie = true;
+        self
+    }
+
+    /// Splits `content` into what must stay at the top of the file, a byte order mark, a shebang
+    /// and an encoding cookie followed by any blank lines, and the rest of the content.
+    pub fn split_preamble<'c>(&self, content: &'c str) -> (&'c str, &'c str) {
+        static CODING_COOKIE: OnceLock<Regex> = OnceLock::new();
+        let coding_cookie = CODING_COOKIE
+            .get_or_init(|| Regex::new(r"^[ \t\f]*#.*?coding[:=][ \t]*[-\w.]+").unwrap());
+
+        let bom = if content.starts_with(BOM) {
+            BOM.len_utf8()
+        } else {
+            0
+        };
+        let mut end = bom;
+
+        for (index, line) in content[bom..].split_inclusive('\n').take(2).enumerate() {
+            // `#![` starts a Rust inner attribute, which rustc does not take for a shebang.
+            let shebang =
+                self.shebang && index == 0 && line.starts_with("#!") && !line.starts_with("#![");
+
+            if shebang || (self.coding_cookie && coding_cookie.is_match(line)) {
+                end += line.len();
+            } else {
+                break;
+            }
+        }
+
+        if end > bom {
+            end += split_blank_lines(&content[end..]).0.len();
+        }
+
+        content.split_at(end)
+    }
+
+    /// Wraps plain text in this comment syntax. Blank lines keep only the trimmed prefix so the
+    /// rendered header never has trailing whitespace.
+    pub fn render(&self, text: &str) -> String {
+        let prefix = match self.syntax {
+            CommentSyntax::Line { prefix } => prefix,
+            CommentSyntax::Block { middle, .. } => middle,
+        };
+
+        let mut lines: Vec<String> = text
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    prefix.trim_end().to_string()
+                } else {
+                    format!("{}{}", prefix, line)
+                }
+            })
+            .collect();
+
+        if let CommentSyntax::Block { start, end, .. } = self.syntax {
+            lines.insert(0, start.to_string());
+            lines.push(end.to_string());
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_line_comment() {
+        let rendered = CommentStyle::DOUBLE_SLASH.render("Copyright 1997 ZhangJian He\n\nLicensed");
+        assert_eq!(rendered, "// Copyright 1997 ZhangJian He\n//\n// Licensed");
+    }
+
+    #[test]
+    fn test_render_block_comment() {
+        let rendered = CommentStyle::SLASH_STAR.render("Copyright 1997 ZhangJian He\n\nLicensed");
+        assert_eq!(
+            rendered,
+            "/*\n * Copyright 1997 ZhangJian He\n *\n * Licensed\n */"
+        );
+
+        let rendered = CommentStyle::XML.render("Copyright 1997 ZhangJian He\n\nLicensed");
+        assert_eq!(
+            rendered,
+            "<!--\n    Copyright 1997 ZhangJian He\n\n    Licensed\n-->"
+        );
+    }
+
+    #[test]
+    fn test_split_preamble() {
+        let python = CommentStyle::HASH.with_shebang().with_coding_cookie();
+        assert_eq!(
+            python.split_preamble("#!/usr/bin/env python3\n# coding: utf-8\n\nx = 1\n"),
+            ("#!/usr/bin/env python3\n# coding: utf-8\n\n", "x = 1\n")
+        );
+        assert_eq!(
+            CommentStyle::HASH.split_preamble("#!/bin/sh\nx: 1\n"),
+            ("", "#!/bin/sh\nx: 1\n"),
+            "Languages without shebangs keep the first line in the body"
+        );
+        assert_eq!(
+            CommentStyle::DOUBLE_SLASH
+                .with_shebang()
+                .split_preamble("#![allow(dead_code)]\nfn main() {}\n"),
+            ("", "#![allow(dead_code)]\nfn main() {}\n"),
+            "Inner attributes are not shebangs"
+        );
+        assert_eq!(
+            python.split_preamble("\u{feff}#!/usr/bin/env python3\nx = 1\n"),
+            ("\u{feff}#!/usr/bin/env python3\n", "x = 1\n")
+        );
+        assert_eq!(
+            CommentStyle::DOUBLE_SLASH.split_preamble("\u{feff}\n// header\n"),
+            ("\u{feff}", "\n// header\n"),
+            "A byte order mark should stay at the very start"
+        );
+    }
+
+    #[test]
+    fn test_split_prologue() {
+        let prologue = CommentStyle::XML.prologue.unwrap();
+        assert_eq!(
+            prologue.split("<?xml version='1.0' encoding=\"utf-8\"?>\n<project/>\n"),
+            Some(("<?xml version='1.0' encoding=\"utf-8\"?>", "<project/>\n"))
+        );
+        assert_eq!(
+            prologue.split("<?xml version=\"1.0\"?>"),
+            Some(("<?xml version=\"1.0\"?>", ""))
+        );
+        assert_eq!(
+            prologue.split("<?xml-stylesheet href=\"style.xsl\"?>\n<project/>\n"),
+            None,
+            "Other processing instructions are not declarations"
+        );
+        assert_eq!(
+            prologue.split("<?xml version=\"1.0\"\n     encoding=\"UTF-8\"?>\r\n<project/>\n"),
+            Some((
+                "<?xml version=\"1.0\"\n     encoding=\"UTF-8\"?>",
+                "<project/>\n"
+            )),
+            "A declaration can span several lines"
+        );
+        assert_eq!(prologue.split("<project/>\n"), None);
+    }
+}
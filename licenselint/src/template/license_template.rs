@@ -0,0 +1,1978 @@
+use crate::config::{Config, ExistingHeader, HeaderStyle};
+use crate::error::{Error, Result};
+use crate::git::CommitYears;
+use crate::issue::{Issue, IssueKind, Span};
+use crate::license::License;
+use crate::template::comment_style::{self, CommentStyle, CommentSyntax};
+use crate::template::LintTemplate;
+use chrono::{DateTime, Datelike, Local};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A license header template for any language, driven by its [`CommentStyle`]. The license text
+/// itself comes from the [`Config`] passed to `check` and `format`.
+pub struct LicenseTemplate {
+    language: &'static str,
+    style: CommentStyle,
+    /// The matchers built so far, one per license, header style and authors.
+    matchers: RwLock<HashMap<MatcherKey, Arc<HeaderMatcher>>>,
+    /// The last commit years looked up by the `cover-last-modified` year policy.
+    commit_years: Arc<CommitYears>,
+}
+
+type MatcherKey = (License, HeaderStyle, Vec<String>);
+
+/// The copyright years of a header: a year, a range like `2019-2024`, or a comma separated list of
+/// both.
+const YEARS: &str = r"\d{4}(?:[ \t]*-[ \t]*\d{4})?(?:[ \t]*,[ \t]*\d{4}(?:[ \t]*-[ \t]*\d{4})?)*";
+
+/// The expected header of a config, compiled once and shared by every file checked with it.
+struct HeaderMatcher {
+    /// The commented header with `{year}` and `{author}` placeholders left in place.
+    header: String,
+    /// Matches the header with any year and any allowed author, anchored at the start.
+    header_regex: Regex,
+    /// Captures the `year` and `author` of the copyright line of the header.
+    copyright_regex: Regex,
+}
+
+impl LicenseTemplate {
+    const SPDX_TEXT: &'static str = r#"SPDX-FileCopyrightText: {year} {author}
+SPDX-License-Identifier: {license}"#;
+
+    pub fn new(language: &'static str, style: CommentStyle) -> Self {
+        LicenseTemplate {
+            language,
+            style,
+            matchers: RwLock::new(HashMap::new()),
+            commit_years: Arc::default(),
+        }
+    }
+
+    /// Shares the last commit years with other templates, so each repository is read once.
+    pub fn with_commit_years(mut self, commit_years: Arc<CommitYears>) -> Self {
+        self.commit_years = commit_years;
+        self
+    }
+
+    /// The matcher for `config`, built on first use.
+    fn matcher(&self, config: &Config) -> Result<Arc<HeaderMatcher>> {
+        let authors: Vec<String> = config
+            .allowed_authors
+            .iter()
+            .chain(std::iter::once(&config.formatted_author))
+            .cloned()
+            .collect();
+        let key = (config.license, config.header_style, authors);
+
+        if let Some(matcher) = self.matchers.read().unwrap().get(&key) {
+            return Ok(matcher.clone());
+        }
+
+        let matcher = Arc::new(self.build_matcher(&key.2, &self.header(config))?);
+        Ok(self
+            .matchers
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert(matcher)
+            .clone())
+    }
+
+    /// The matcher for `config`, which only fails to build for configs `prepare` rejects.
+    fn prepared_matcher(&self, config: &Config) -> Arc<HeaderMatcher> {
+        self.matcher(config)
+            .unwrap_or_else(|e| panic!("template used with a config it rejects: {}", e))
+    }
+
+    fn build_matcher(&self, authors: &[String], header: &str) -> Result<HeaderMatcher> {
+        let authors: Vec<String> = authors.iter().map(|author| regex::escape(author)).collect();
+
+        // Files may end their lines with CRLF and trailing whitespace, like `first_difference`
+        // allows.
+        let expected_license = regex::escape(header)
+            .replace(r"\{author\}", &format!("(?:{})", authors.join("|")))
+            .replace(r"\{year\}", YEARS)
+            .replace('\n', r"[ \t]*\r?\n");
+
+        let copyright_line = header
+            .lines()
+            .find(|line| line.contains("{year}"))
+            .unwrap_or_default();
+        let copyright_pattern = regex::escape(copyright_line.trim_end())
+            .replace(r"\{year\}", &format!("(?P<year>{}|.*?)", YEARS))
+            .replace(r"\{author\}", r"(?P<author>.*)");
+
+        let regex = |pattern: String| {
+            Regex::new(&pattern).map_err(|e| Error::Template {
+                language: self.language.to_string(),
+                message: e.to_string(),
+            })
+        };
+
+        Ok(HeaderMatcher {
+            header: header.to_string(),
+            header_regex: regex(format!(r"\A{}", expected_license))?,
+            copyright_regex: regex(format!("^{}$", copyright_pattern))?,
+        })
+    }
+
+    /// The commented header with `{year}` and `{author}` placeholders left in place.
+    fn header(&self, config: &Config) -> String {
+        self.render_header(config.license, config.header_style)
+    }
+
+    fn render_header(&self, license: License, header_style: HeaderStyle) -> String {
+        let text = match header_style {
+            HeaderStyle::Full => license.text().to_string(),
+            HeaderStyle::Spdx => Self::SPDX_TEXT.replace("{license}", license.to_str()),
+        };
+
+        self.style.render(&text)
+    }
+
+    /// The comment block at the very start of `body`, without its trailing line break.
+    fn leading_comment<'c>(&self, body: &'c str) -> Option<&'c str> {
+        let end = match self.style.syntax {
+            CommentSyntax::Line { prefix } => body
+                .split_inclusive('\n')
+                .take_while(|line| {
+                    let line = line.trim_end();
+                    line == prefix.trim_end() || line.starts_with(prefix)
+                })
+                .map(|line| line.len())
+                .sum(),
+            CommentSyntax::Block { start, end, .. } => {
+                if !body.starts_with(start) {
+                    return None;
+                }
+
+                let end = end.trim();
+                body[start.len()..].find(end)? + start.len() + end.len()
+            }
+        };
+
+        let comment = body[..end].trim_end_matches(['\r', '\n']);
+
+        if comment.is_empty() {
+            None
+        } else {
+            Some(comment)
+        }
+    }
+
+    /// The license header at the start of `comment`, the leading comment of a file, or `None` when
+    /// the comment is ordinary documentation. A header either matches one of the supported headers
+    /// apart from its copyright line, or starts with an SPDX tag or a `Copyright <year>` line. In
+    /// a run of line comments, the paragraphs after such a header that do not mention licensing
+    /// are not part of it.
+    fn license_header<'c>(&self, comment: &'c str) -> Option<&'c str> {
+        let lines: Vec<&str> = comment.split_inclusive('\n').collect();
+
+        for license in License::ALL {
+            for header_style in [HeaderStyle::Full, HeaderStyle::Spdx] {
+                let header = self.render_header(license, header_style);
+                let count = header.lines().count();
+
+                if count <= lines.len() {
+                    let end: usize = lines[..count].iter().map(|line| line.len()).sum();
+                    let existing = comment[..end].trim_end_matches(['\r', '\n']);
+                    if first_difference(&header, existing).is_none() {
+                        return Some(existing);
+                    }
+                }
+            }
+        }
+
+        static MARKER: OnceLock<Regex> = OnceLock::new();
+        let marker = MARKER.get_or_init(|| {
+            Regex::new(r"(?im)SPDX-(?:License-Identifier|FileCopyrightText):|copyright[ \t]+(?:\(c\)[ \t]*|©[ \t]*)?\d{4}")
+                .unwrap()
+        });
+        static LICENSE_TERMS: OnceLock<Regex> = OnceLock::new();
+        let license_terms = LICENSE_TERMS.get_or_init(|| {
+            Regex::new(r"(?i)copyright|licen[cs]e|spdx-|warrant|redistribut|permission").unwrap()
+        });
+
+        let blank = match self.style.syntax {
+            CommentSyntax::Line { prefix } => prefix.trim_end(),
+            CommentSyntax::Block { start, middle, .. } => {
+                // Like the first paragraph of line comments, the first line of a block comment
+                // must be the copyright or SPDX line, so a doc comment mentioning copyright is
+                // not taken for a header.
+                let first_line = comment
+                    .lines()
+                    .map(|line| {
+                        let line = line
+                            .trim()
+                            .strip_prefix(start.trim())
+                            .unwrap_or(line.trim());
+                        let middle = middle.trim();
+                        let line = if middle.is_empty() {
+                            line
+                        } else {
+                            line.trim_start_matches(middle)
+                        };
+                        line.trim()
+                    })
+                    .find(|line| !line.is_empty())?;
+
+                return marker
+                    .find(first_line)
+                    .filter(|m| m.start() == 0)
+                    .map(|_| comment);
+            }
+        };
+
+        // Paragraphs of the comment as byte ranges, split on blank comment lines.
+        let mut paragraphs: Vec<(usize, usize)> = Vec::new();
+        let mut offset = 0;
+        for line in &lines {
+            if line.trim_end() != blank {
+                match paragraphs.last_mut() {
+                    Some((_, end)) if *end == offset => *end += line.len(),
+                    _ => paragraphs.push((offset, offset + line.len())),
+                }
+            }
+            offset += line.len();
+        }
+
+        let (first, rest) = paragraphs.split_first()?;
+        if first.0 != 0 || !marker.is_match(&comment[first.0..first.1]) {
+            return None;
+        }
+
+        let end = rest
+            .iter()
+            .take_while(|(start, end)| license_terms.is_match(&comment[*start..*end]))
+            .last()
+            .map_or(first.1, |(_, end)| *end);
+
+        Some(comment[..end].trim_end_matches(['\r', '\n']))
+    }
+
+    /// Rewrites the copyright line of `existing` with the formatted author, keeping its year, when
+    /// every other line already matches the expected header.
+    fn update_header(
+        &self,
+        config: &Config,
+        filename: &Path,
+        expected: &str,
+        existing: &str,
+    ) -> Option<String> {
+        static YEAR: OnceLock<Regex> = OnceLock::new();
+        let year_re = YEAR.get_or_init(|| Regex::new(YEARS).unwrap());
+
+        if first_difference(expected, existing).is_some() {
+            return None;
+        }
+
+        let year = expected
+            .lines()
+            .zip(existing.lines())
+            .find(|(expected_line, _)| expected_line.contains("{year}"))
+            .and_then(|(_, existing_line)| year_re.find(existing_line))
+            .map(|m| {
+                covered_years(config, &self.commit_years, filename, m.as_str())
+                    .unwrap_or_else(|| m.as_str().to_string())
+            });
+
+        Some(
+            expected
+                .replace("{year}", year.as_deref().unwrap_or(&config.formatted_year))
+                .replace("{author}", &config.formatted_author),
+        )
+    }
+
+    /// `body` with the copyright years of the header matched at `start` extended to the year
+    /// `filename` was last modified, when the year policy asks to cover it.
+    fn cover_last_modified(
+        &self,
+        config: &Config,
+        matcher: &HeaderMatcher,
+        filename: &Path,
+        body: &str,
+        start: usize,
+    ) -> Option<String> {
+        let index = matcher
+            .header
+            .lines()
+            .position(|expected_line| expected_line.contains("{year}"))?;
+        let line_start = start
+            + body[start..]
+                .split_inclusive('\n')
+                .take(index)
+                .map(str::len)
+                .sum::<usize>();
+        let existing_line = body[line_start..].lines().next()?;
+        let years = matcher
+            .copyright_regex
+            .captures(existing_line.trim_end())?
+            .name("year")?;
+        let covered = covered_years(config, &self.commit_years, filename, years.as_str())?;
+
+        Some(format!(
+            "{}{}{}",
+            &body[..line_start + years.start()],
+            covered,
+            &body[line_start + years.end()..]
+        ))
+    }
+
+    /// Checks the header at the start of `body`, which begins on line `line` of the file.
+    fn check_header(
+        &self,
+        config: &Config,
+        filename: &Path,
+        body: &str,
+        line: usize,
+    ) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let matcher = self.prepared_matcher(config);
+
+        let (blank, body) = comment_style::split_blank_lines(body);
+        let line = line + blank.matches('\n').count();
+
+        if let Some(m) = matcher.header_regex.find(body) {
+            let index = matcher
+                .header
+                .lines()
+                .position(|expected_line| expected_line.contains("{year}"));
+            let existing_line = index.and_then(|index| m.as_str().lines().nth(index));
+            let years = existing_line.and_then(|existing_line| {
+                matcher.copyright_regex.captures(existing_line.trim_end())
+            });
+
+            if let (Some(index), Some(existing_line), Some(years)) = (index, existing_line, years) {
+                issues.extend(self.check_years(
+                    config,
+                    filename,
+                    &years["year"],
+                    existing_line,
+                    line + index,
+                ));
+            }
+
+            let rest = &body[m.end()..];
+            let next = rest.trim_start_matches(['\r', '\n']);
+
+            if let Some(duplicate) = self
+                .leading_comment(next)
+                .and_then(|comment| self.license_header(comment))
+            {
+                let duplicate_line = line + body[..body.len() - next.len()].matches('\n').count();
+                let first_line = duplicate.lines().next().unwrap_or_default();
+                issues.push(Issue::new(
+                    filename,
+                    IssueKind::DuplicateHeader,
+                    Span::line(duplicate_line, first_line),
+                    "duplicate license header after the expected one".to_string(),
+                ));
+            }
+
+            return issues;
+        }
+
+        let existing = match self
+            .leading_comment(body)
+            .and_then(|comment| self.license_header(comment))
+        {
+            Some(existing) => existing,
+            None => {
+                let first_line = body.lines().next().unwrap_or_default();
+                issues.push(Issue::new(
+                    filename,
+                    IssueKind::MissingHeader,
+                    Span::line(line, first_line),
+                    format!("missing {} license header", config.license.to_str()),
+                ));
+                return issues;
+            }
+        };
+
+        let expected = &matcher.header;
+        let existing_lines: Vec<&str> = existing.lines().collect();
+
+        let index = match first_difference(expected, existing) {
+            Some(index) => index,
+            None => {
+                let index = expected
+                    .lines()
+                    .position(|expected_line| expected_line.contains("{year}"))
+                    .unwrap_or_default();
+                let template_line = expected.lines().nth(index).unwrap_or_default();
+                let existing_line = existing_lines.get(index).copied().unwrap_or_default();
+
+                issues.extend(self.check_copyright_line(
+                    config,
+                    &matcher.copyright_regex,
+                    filename,
+                    template_line,
+                    existing_line,
+                    line + index,
+                ));
+                return issues;
+            }
+        };
+
+        let first_line = existing_lines.first().copied().unwrap_or_default();
+
+        for license in License::ALL {
+            for header_style in [HeaderStyle::Full, HeaderStyle::Spdx] {
+                if license == config.license && header_style == config.header_style {
+                    continue;
+                }
+
+                if first_difference(&self.render_header(license, header_style), existing).is_some()
+                {
+                    continue;
+                }
+
+                let issue = if license == config.license {
+                    Issue::new(
+                        filename,
+                        IssueKind::MalformedHeader,
+                        Span::line(line, first_line),
+                        format!(
+                            "expected a {} header, found a {} header",
+                            header_style_name(config.header_style),
+                            header_style_name(header_style)
+                        ),
+                    )
+                } else {
+                    Issue::new(
+                        filename,
+                        IssueKind::WrongLicense,
+                        Span::line(line, first_line),
+                        format!(
+                            "wrong license: expected {}, found {}",
+                            config.license.to_str(),
+                            license.to_str()
+                        ),
+                    )
+                    .with_expected_found(config.license.to_str(), license.to_str())
+                };
+
+                issues.push(issue);
+                return issues;
+            }
+        }
+
+        static SPDX_IDENTIFIER: OnceLock<Regex> = OnceLock::new();
+        let spdx_identifier = SPDX_IDENTIFIER
+            .get_or_init(|| Regex::new(r"SPDX-License-Identifier:\s*(\S+)").unwrap());
+
+        for (offset, existing_line) in existing_lines.iter().enumerate() {
+            if let Some(captures) = spdx_identifier.captures(existing_line) {
+                let found = &captures[1];
+                if found != config.license.to_str() {
+                    issues.push(
+                        Issue::new(
+                            filename,
+                            IssueKind::WrongLicense,
+                            Span::line(line + offset, existing_line),
+                            format!(
+                                "wrong license: expected {}, found {}",
+                                config.license.to_str(),
+                                found
+                            ),
+                        )
+                        .with_expected_found(config.license.to_str(), found),
+                    );
+                    return issues;
+                }
+            }
+        }
+
+        let expected_line = expected
+            .lines()
+            .nth(index)
+            .unwrap_or("<end of header>")
+            .replace("{year}", &config.formatted_year)
+            .replace("{author}", &config.formatted_author);
+        let found_line = existing_lines
+            .get(index)
+            .copied()
+            .unwrap_or("<end of header>");
+
+        issues.push(
+            Issue::new(
+                filename,
+                IssueKind::MalformedHeader,
+                Span::line(
+                    line + index.min(existing_lines.len().saturating_sub(1)),
+                    found_line,
+                ),
+                format!(
+                    "malformed license header: expected '{}', found '{}'",
+                    expected_line, found_line
+                ),
+            )
+            .with_expected_found(&expected_line, found_line),
+        );
+
+        issues
+    }
+
+    /// Checks the copyright `years` of `existing_line` against the year policy of `config`.
+    fn check_years(
+        &self,
+        config: &Config,
+        filename: &Path,
+        years: &str,
+        existing_line: &str,
+        line: usize,
+    ) -> Option<Issue> {
+        let issue = |message: String, expected: &str| {
+            Some(
+                Issue::new(
+                    filename,
+                    IssueKind::WrongYear,
+                    Span::line(line, existing_line),
+                    message,
+                )
+                .with_expected_found(expected, years),
+            )
+        };
+
+        let ranges = match parse_years(years) {
+            Some(ranges) => ranges,
+            None => {
+                return issue(
+                    format!(
+                    "invalid copyright year: expected a year, range or list like '{}', found '{}'",
+                    config.formatted_year, years
+                ),
+                    &config.formatted_year,
+                )
+            }
+        };
+        let first = ranges.iter().map(|(start, _)| *start).min()?;
+        let last = ranges.iter().map(|(_, end)| *end).max()?;
+        let policy = &config.year_policy;
+
+        if let Some(start) = policy.start.filter(|&start| first < start) {
+            return issue(
+                format!(
+                    "copyright year {} is before the project start year {}",
+                    first, start
+                ),
+                &start.to_string(),
+            );
+        }
+
+        if let Some(end) = policy.end.filter(|&end| last > end) {
+            return issue(
+                format!("copyright year {} is in the future", last),
+                &end.to_string(),
+            );
+        }
+
+        if policy.cover_last_modified {
+            let modified = last_modified_year(&self.commit_years, filename);
+            if last < modified {
+                return issue(
+                    format!(
+                        "copyright year {} does not cover the last modification in {}",
+                        last, modified
+                    ),
+                    &format!("{}-{}", first, modified),
+                );
+            }
+        }
+
+        None
+    }
+
+    /// Checks the year and author of a copyright line whose template is `template_line`.
+    fn check_copyright_line(
+        &self,
+        config: &Config,
+        re: &Regex,
+        filename: &Path,
+        template_line: &str,
+        existing_line: &str,
+        line: usize,
+    ) -> Vec<Issue> {
+        let mut issues = Vec::new();
+
+        let expected_line = template_line
+            .replace("{year}", &config.formatted_year)
+            .replace("{author}", &config.formatted_author);
+
+        let captures = match re.captures(existing_line.trim_end()) {
+            Some(captures) => captures,
+            None => {
+                issues.push(
+                    Issue::new(
+                        filename,
+                        IssueKind::MalformedHeader,
+                        Span::line(line, existing_line),
+                        format!(
+                            "malformed copyright line: expected '{}', found '{}'",
+                            expected_line, existing_line
+                        ),
+                    )
+                    .with_expected_found(&expected_line, existing_line),
+                );
+                return issues;
+            }
+        };
+
+        let author = &captures["author"];
+
+        issues.extend(self.check_years(config, filename, &captures["year"], existing_line, line));
+
+        if author != config.formatted_author && !config.allowed_authors.iter().any(|a| a == author)
+        {
+            issues.push(
+                Issue::new(
+                    filename,
+                    IssueKind::WrongAuthor,
+                    Span::line(line, existing_line),
+                    format!(
+                        "copyright holder is not allowed: expected '{}', found '{}'",
+                        config.formatted_author, author
+                    ),
+                )
+                .with_expected_found(&config.formatted_author, author),
+            );
+        }
+
+        issues
+    }
+}
+
+/// Parses copyright years like `2019`, `2019-2024` or `2019, 2021-2023` into ranges of years.
+fn parse_years(years: &str) -> Option<Vec<(i32, i32)>> {
+    let parse_year = |year: &str| {
+        let year = year.trim();
+        if year.len() == 4 && year.bytes().all(|b| b.is_ascii_digit()) {
+            year.parse().ok()
+        } else {
+            None
+        }
+    };
+
+    years
+        .split(',')
+        .map(|range| {
+            let (start, end) = range.split_once('-').unwrap_or((range, range));
+            let (start, end) = (parse_year(start)?, parse_year(end)?);
+            (start <= end).then_some((start, end))
+        })
+        .collect()
+}
+
+/// `years` with its last range extended to the year `filename` was last modified, when the year
+/// policy asks to cover it and `years` does not.
+fn covered_years(
+    config: &Config,
+    commit_years: &CommitYears,
+    filename: &Path,
+    years: &str,
+) -> Option<String> {
+    if !config.year_policy.cover_last_modified {
+        return None;
+    }
+
+    let ranges = parse_years(years)?;
+    let last = ranges.iter().map(|(_, end)| *end).max()?;
+    let modified = last_modified_year(commit_years, filename);
+    if last >= modified {
+        return None;
+    }
+
+    let (start, _) = *ranges.last()?;
+    let (head, tail) = years.split_at(years.rfind(',').map_or(0, |index| index + 1));
+    let indent = &tail[..tail.len() - tail.trim_start().len()];
+
+    Some(format!("{}{}{}-{}", head, indent, start, modified))
+}
+
+/// The year `path` was last modified: the year of its last commit, or of its modification time when
+/// it is untracked or has uncommitted changes, and the current year for files that are not on disk.
+fn last_modified_year(commit_years: &CommitYears, path: &Path) -> i32 {
+    if let Some(year) = commit_years.last_commit_year(path) {
+        return year;
+    }
+
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_or_else(
+            |_| Local::now().year(),
+            |modified| DateTime::<Local>::from(modified).year(),
+        )
+}
+
+/// The line ending of the first line of `content`, `\n` when it has a single line.
+fn line_ending(content: &str) -> &'static str {
+    match content.split_once('\n') {
+        Some((line, _)) if line.ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+/// Compares `existing` with the header `template` line by line, skipping the copyright line, and
+/// returns the index of the first line that differs.
+fn first_difference(template: &str, existing: &str) -> Option<usize> {
+    let template_lines: Vec<&str> = template.lines().collect();
+    let existing_lines: Vec<&str> = existing.lines().collect();
+
+    (0..template_lines.len().max(existing_lines.len())).find(|&index| {
+        match (template_lines.get(index), existing_lines.get(index)) {
+            (Some(template_line), Some(_)) if template_line.contains("{year}") => false,
+            (Some(template_line), Some(existing_line)) => {
+                template_line.trim_end() != existing_line.trim_end()
+            }
+            _ => true,
+        }
+    })
+}
+
+fn header_style_name(header_style: HeaderStyle) -> &'static str {
+    match header_style {
+        HeaderStyle::Full => "full",
+        HeaderStyle::Spdx => "SPDX",
+    }
+}
+
+impl LintTemplate for LicenseTemplate {
+    fn language(&self) -> &str {
+        self.language
+    }
+
+    fn prepare(&self, config: &Config) -> Result<()> {
+        self.matcher(config).map(|_| ())
+    }
+
+    fn check(&self, config: &Config, filename: &Path, content: &str) -> Vec<Issue> {
+        let (preamble, body) = self.style.split_preamble(content);
+        let line = preamble.matches('\n').count() + 1;
+
+        let prologue = match self.style.prologue {
+            Some(prologue) => prologue,
+            None => return self.check_header(config, filename, body, line),
+        };
+
+        if let Some((_, rest)) = prologue.split(body) {
+            let prologue_lines = body[..body.len() - rest.len()].matches('\n').count();
+            return self.check_header(config, filename, rest, line + prologue_lines);
+        }
+
+        let mut issues = self.check_header(config, filename, body, line);
+
+        if issues.is_empty() {
+            let first_line = body.lines().next().unwrap_or_default();
+            issues.push(
+                Issue::new(
+                    filename,
+                    IssueKind::MalformedHeader,
+                    Span::line(line, first_line),
+                    format!("expected '{}' before the license header", prologue.line),
+                )
+                .with_expected_found(prologue.line, first_line),
+            );
+        }
+
+        issues
+    }
+
+    fn format(&self, config: &Config, filename: &Path, content: &str) -> String {
+        let matcher = self.prepared_matcher(config);
+        // Inserted lines end like the first line of the file, so CRLF files stay CRLF.
+        let newline = line_ending(content);
+        let license_text = matcher
+            .header
+            .replace("{year}", &config.formatted_year)
+            .replace("{author}", &config.formatted_author)
+            .replace('\n', newline);
+
+        let (preamble, body) = self.style.split_preamble(content);
+        let preamble = if preamble.trim_start_matches(comment_style::BOM).is_empty()
+            || preamble.ends_with('\n')
+        {
+            preamble.to_string()
+        } else {
+            format!("{}{}", preamble, newline)
+        };
+
+        let (prologue, separator, body) = match self.style.prologue {
+            Some(prologue) => match prologue.split(body) {
+                Some((line, rest)) => (format!("{}{}", line, newline), newline.to_string(), rest),
+                // A declaration without its end is broken, but a second one would be worse.
+                None if prologue.starts(body) => (String::new(), newline.to_string(), body),
+                None => (
+                    format!("{}{}", prologue.line, newline),
+                    newline.to_string(),
+                    body,
+                ),
+            },
+            None => (String::new(), newline.repeat(2), body),
+        };
+
+        // A header below blank lines is kept where it is rather than getting a second one on top.
+        let (blank, rest) = comment_style::split_blank_lines(body);
+
+        if let Some(m) = matcher.header_regex.find(rest) {
+            let body = self
+                .cover_last_modified(config, &matcher, filename, body, blank.len() + m.start())
+                .unwrap_or_else(|| body.to_string());
+            return format!("{}{}{}", preamble, prologue, body);
+        }
+
+        let existing = self
+            .leading_comment(rest)
+            .and_then(|comment| self.license_header(comment));
+
+        let body = match existing {
+            Some(existing) => {
+                let header = match config.existing_header {
+                    ExistingHeader::Update => self
+                        .update_header(config, filename, &matcher.header, existing)
+                        .map_or(license_text, |header| header.replace('\n', newline)),
+                    ExistingHeader::Replace => license_text,
+                };
+
+                let after = &rest[existing.len()..];
+                let code = after.trim_start_matches(['\r', '\n']);
+
+                if code.is_empty() {
+                    format!("{}{}{}", blank, header, after)
+                } else {
+                    format!("{}{}{}{}", blank, header, separator, code)
+                }
+            }
+            _ => format!("{}{}{}", license_text, separator, body),
+        };
+
+        format!("{}{}{}", preamble, prologue, body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::license::License;
+
+    fn test_config(license: License) -> Config {
+        Config::new_from_author(license, "ZhangJian He".to_string(), "1997".to_string())
+    }
+
+    #[test]
+    fn test_check_valid_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let config = test_config(License::Apache20);
+
+        let content = r#"// Copyright 1997 ZhangJian He
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License."#;
+
+        let issues = template.check(&config, Path::new("main.cpp"), content);
+        assert!(
+            issues.is_empty(),
+            "There should be no issues with a valid license"
+        );
+    }
+
+    #[test]
+    fn test_check_invalid_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let config = test_config(License::Apache20);
+
+        let content = r#"// Some other license
+//
+// Licensed under the XYZ License
+"#;
+
+        let issues = template.check(&config, Path::new("main.cpp"), content);
+        assert_eq!(
+            issues.len(),
+            1,
+            "There should be one issue for an invalid license"
+        );
+    }
+
+    #[test]
+    fn test_format_no_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let config = test_config(License::Apache20);
+
+        let content = r#"int main() {
+    return 0;
+}
+"#;
+
+        let expected = r#"// Copyright 1997 ZhangJian He
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+int main() {
+    return 0;
+}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.cpp"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "The license should be added to the content"
+        );
+    }
+
+    #[test]
+    fn test_format_with_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let config = test_config(License::Apache20);
+
+        let content = r#"// Copyright 1997 ZhangJian He
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+int main() {
+    return 0;
+}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.cpp"), content);
+        assert_eq!(
+            formatted_content, content,
+            "Content should remain unchanged if the license is already present"
+        );
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let config = test_config(License::Apache20);
+
+        let header = template
+            .header(&config)
+            .replace("{year}", "1997")
+            .replace("{author}", "ZhangJian He")
+            .replace('\n', "\r\n");
+        let content = format!("{}\r\n\r\nint main() {{}}\r\n", header);
+        assert!(
+            template
+                .check(&config, Path::new("main.cpp"), &content)
+                .is_empty(),
+            "A header with CRLF line endings should be accepted"
+        );
+        assert_eq!(
+            template.format(&config, Path::new("main.cpp"), &content),
+            content
+        );
+
+        assert_eq!(
+            template.format(&config, Path::new("main.cpp"), "int main() {}\r\n"),
+            format!("{}\r\n\r\nint main() {{}}\r\n", header),
+            "An inserted header should use the line ending of the file"
+        );
+
+        let outdated = content.replace("ZhangJian He", "Old Author");
+        assert_eq!(
+            template.format(&config, Path::new("main.cpp"), &outdated),
+            content,
+            "An updated header should keep the line ending of the file"
+        );
+    }
+
+    #[test]
+    fn test_check_allowed_authors() {
+        let template = LicenseTemplate::new("test", CommentStyle::HASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+        config.add_allowed_author("Partner Inc.".to_string());
+
+        let content = r#"# SPDX-FileCopyrightText: 2020 Partner Inc.
+# SPDX-License-Identifier: Apache-2.0
+"#;
+
+        let issues = template.check(&config, Path::new("main.py"), content);
+        assert!(
+            issues.is_empty(),
+            "A header from any allowed author should be accepted"
+        );
+
+        let content = r#"# SPDX-FileCopyrightText: 2020 Someone Else
+# SPDX-License-Identifier: Apache-2.0
+"#;
+
+        let issues = template.check(&config, Path::new("main.py"), content);
+        assert_eq!(
+            issues.len(),
+            1,
+            "A header from an author that is not allowed should be reported"
+        );
+
+        let formatted_content = template.format(&config, Path::new("main.py"), "x = 1\n");
+        assert!(
+            formatted_content.starts_with("# SPDX-FileCopyrightText: 1997 ZhangJian He\n"),
+            "Format should insert the formatted author"
+        );
+    }
+
+    #[test]
+    fn test_matcher_is_cached() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let config = test_config(License::Apache20);
+
+        template.prepare(&config).unwrap();
+        template.check(&config, Path::new("a.rs"), "fn a() {}\n");
+        template.format(&config, Path::new("b.rs"), "fn b() {}\n");
+        assert_eq!(
+            template.matchers.read().unwrap().len(),
+            1,
+            "Files checked with the same config should share one matcher"
+        );
+
+        let mut config = config.clone();
+        config.add_allowed_author("Partner Inc.".to_string());
+        template.check(&config, Path::new("a.rs"), "fn a() {}\n");
+        assert_eq!(template.matchers.read().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_format_keeps_shebang() {
+        let template = LicenseTemplate::new(
+            "test",
+            CommentStyle::HASH.with_shebang().with_coding_cookie(),
+        );
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+print("hello")
+"#;
+
+        let expected = r#"#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 1997 ZhangJian He
+# SPDX-License-Identifier: Apache-2.0
+
+print("hello")
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.py"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "The header should be inserted after the shebang and encoding cookie"
+        );
+        assert!(
+            template
+                .check(&config, Path::new("main.py"), &formatted_content)
+                .is_empty(),
+            "A header after the shebang should be accepted"
+        );
+        assert_eq!(
+            template.format(&config, Path::new("main.py"), &formatted_content),
+            formatted_content,
+            "Content should remain unchanged if the header is already present"
+        );
+
+        let formatted_content =
+            template.format(&config, Path::new("run.py"), "#!/usr/bin/env python3");
+        assert_eq!(
+            formatted_content,
+            "#!/usr/bin/env python3\n# SPDX-FileCopyrightText: 1997 ZhangJian He\n# SPDX-License-Identifier: Apache-2.0\n\n"
+        );
+    }
+
+    #[test]
+    fn test_format_rust_inner_attribute() {
+        let template = LicenseTemplate::new("rust", CommentStyle::DOUBLE_SLASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = "#![allow(dead_code)]\nfn main() {}\n";
+        assert_eq!(
+            template.check(&config, Path::new("lib.rs"), content)[0].kind,
+            IssueKind::MissingHeader
+        );
+        assert_eq!(
+            template.format(&config, Path::new("lib.rs"), content),
+            "// SPDX-FileCopyrightText: 1997 ZhangJian He\n// SPDX-License-Identifier: Apache-2.0\n\n#![allow(dead_code)]\nfn main() {}\n",
+            "The header should be inserted above inner attributes"
+        );
+        assert_eq!(
+            template.check(
+                &config,
+                Path::new("lib.rs"),
+                "#![allow(dead_code)]\n// SPDX-FileCopyrightText: 1997 ZhangJian He\n// SPDX-License-Identifier: Apache-2.0\n"
+            )[0]
+            .kind,
+            IssueKind::MissingHeader,
+            "A header below an inner attribute should not be accepted"
+        );
+    }
+
+    #[test]
+    fn test_header_after_leading_blank_line() {
+        let template = LicenseTemplate::new("rust", CommentStyle::DOUBLE_SLASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = "\n// SPDX-FileCopyrightText: 1997 ZhangJian He\n// SPDX-License-Identifier: Apache-2.0\n\nfn main() {}\n";
+        assert!(template
+            .check(&config, Path::new("main.rs"), content)
+            .is_empty());
+        assert_eq!(
+            template.format(&config, Path::new("main.rs"), content),
+            content,
+            "No second header should be added above blank lines"
+        );
+
+        let content = "\n// SPDX-FileCopyrightText: 2019 Old Author\n// SPDX-License-Identifier: Apache-2.0\n\nfn main() {}\n";
+        assert_eq!(
+            template.format(&config, Path::new("main.rs"), content),
+            "\n// SPDX-FileCopyrightText: 2019 ZhangJian He\n// SPDX-License-Identifier: Apache-2.0\n\nfn main() {}\n",
+            "A header below blank lines should be updated in place"
+        );
+    }
+
+    #[test]
+    fn test_header_after_byte_order_mark() {
+        let template = LicenseTemplate::new("rust", CommentStyle::DOUBLE_SLASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let header = "// SPDX-FileCopyrightText: 1997 ZhangJian He\n// SPDX-License-Identifier: Apache-2.0\n";
+        let formatted = template.format(&config, Path::new("main.rs"), "\u{feff}fn main() {}\n");
+        assert_eq!(
+            formatted,
+            format!("\u{feff}{}\nfn main() {{}}\n", header),
+            "The header should be inserted after the byte order mark"
+        );
+        assert!(template
+            .check(&config, Path::new("main.rs"), &formatted)
+            .is_empty());
+        assert_eq!(
+            template.format(&config, Path::new("main.rs"), &formatted),
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_header_after_blank_line_below_shebang() {
+        let template = LicenseTemplate::new(
+            "test",
+            CommentStyle::HASH.with_shebang().with_coding_cookie(),
+        );
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"#!/usr/bin/env python3
+
+# SPDX-FileCopyrightText: 1997 ZhangJian He
+# SPDX-License-Identifier: Apache-2.0
+
+print("hello")
+"#;
+
+        assert!(
+            template
+                .check(&config, Path::new("main.py"), content)
+                .is_empty(),
+            "A header separated from the shebang by a blank line should be accepted"
+        );
+        assert_eq!(
+            template.format(&config, Path::new("main.py"), content),
+            content,
+            "No second header should be added"
+        );
+
+        let issues = template.check(
+            &config,
+            Path::new("main.py"),
+            "#!/usr/bin/env python3\n\nprint(1)\n",
+        );
+        assert_eq!(issues[0].kind, IssueKind::MissingHeader);
+        assert_eq!(issues[0].span.start_line, 3);
+    }
+
+    #[test]
+    fn test_check_header_not_at_top() {
+        let template = LicenseTemplate::new("test", CommentStyle::HASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"import os
+
+# SPDX-FileCopyrightText: 1997 ZhangJian He
+# SPDX-License-Identifier: Apache-2.0
+"#;
+
+        let issues = template.check(&config, Path::new("main.py"), content);
+        assert_eq!(
+            issues.len(),
+            1,
+            "A header below other code should be reported"
+        );
+    }
+
+    #[test]
+    fn test_format_updates_existing_header() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"// SPDX-FileCopyrightText: 2019 Old Author
+// SPDX-License-Identifier: Apache-2.0
+
+int main() {
+    return 0;
+}
+"#;
+
+        let expected = r#"// SPDX-FileCopyrightText: 2019 ZhangJian He
+// SPDX-License-Identifier: Apache-2.0
+
+int main() {
+    return 0;
+}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.cpp"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "The author should be updated in place, keeping the year"
+        );
+
+        config.existing_header = ExistingHeader::Replace;
+
+        let formatted_content = template.format(&config, Path::new("main.cpp"), content);
+        assert_eq!(
+            formatted_content,
+            expected.replace("2019", "1997"),
+            "The whole header should be replaced"
+        );
+    }
+
+    #[test]
+    fn test_format_replaces_foreign_header() {
+        let template = LicenseTemplate::new("test", CommentStyle::SLASH_STAR);
+        let mut config = test_config(License::Mit);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"/*
+ * Copyright (C) 2015 Someone Else
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ */
+package main;
+"#;
+
+        let expected = r#"/*
+ * SPDX-FileCopyrightText: 1997 ZhangJian He
+ * SPDX-License-Identifier: MIT
+ */
+
+package main;
+"#;
+
+        let formatted_content = template.format(&config, Path::new("Main.java"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "A foreign header should be replaced instead of kept below the new one"
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_doc_comment_mentioning_copyright() {
+        let template = LicenseTemplate::new("test", CommentStyle::SLASH_STAR);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"/**
+ * Utility class. Copyright 2020 notes are kept in NOTICE.
+ */
+public class Util {}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("Util.java"), content);
+        assert!(
+            formatted_content.ends_with(&format!("\n\n{}", content)),
+            "A doc comment that only mentions copyright should be kept"
+        );
+        assert_eq!(
+            template.check(&config, Path::new("Util.java"), content)[0].kind,
+            IssueKind::MissingHeader
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_other_comments() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"// Entry point of the tool.
+int main() {
+    return 0;
+}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.cpp"), content);
+        assert!(
+            formatted_content.ends_with(content),
+            "A comment that is not a license header should be kept"
+        );
+
+        let content = r#"// SPDX-FileCopyrightText: 2019 ZhangJian He
+// SPDX-License-Identifier: Apache-2.0
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.cpp"), content);
+        assert_eq!(
+            formatted_content, content,
+            "A valid header from another year should be kept"
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_comments_about_licenses() {
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let template = LicenseTemplate::new("test", CommentStyle::HASH);
+        let content = "# Tool that checks license headers in the repo.\n# Usage: python check.py\nimport os\n";
+        let issues = template.check(&config, Path::new("check.py"), content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::MissingHeader);
+        assert_eq!(
+            template.format(&config, Path::new("check.py"), content),
+            format!(
+                "# SPDX-FileCopyrightText: 1997 ZhangJian He\n# SPDX-License-Identifier: Apache-2.0\n\n{}",
+                content
+            ),
+            "A comment mentioning licenses should be kept below the new header"
+        );
+
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let content = "// This module parses the LICENSE file.\nmod parser;\n";
+        let formatted_content = template.format(&config, Path::new("lib.rs"), content);
+        assert!(formatted_content.ends_with(&format!("\n\n{}", content)));
+        assert!(
+            template
+                .check(&config, Path::new("lib.rs"), &formatted_content)
+                .is_empty(),
+            "A comment below the header should not be reported as a duplicate header"
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_comment_below_old_header() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"// SPDX-FileCopyrightText: 2019 Old Author
+// SPDX-License-Identifier: Apache-2.0
+// Parses the configuration file.
+mod config;
+"#;
+
+        let expected = r#"// SPDX-FileCopyrightText: 2019 ZhangJian He
+// SPDX-License-Identifier: Apache-2.0
+
+// Parses the configuration file.
+mod config;
+"#;
+
+        assert_eq!(
+            template.format(&config, Path::new("lib.rs"), content),
+            expected,
+            "Only the header lines should be updated, followed by a blank line like a new header"
+        );
+
+        config.existing_header = ExistingHeader::Replace;
+        assert_eq!(
+            template.format(&config, Path::new("lib.rs"), content),
+            expected.replace("2019", "1997"),
+            "Only the header lines should be replaced"
+        );
+
+        let content = r#"// Copyright 2015 Someone Else
+//
+// Distributed under the Boost Software License.
+
+// Parses the configuration file.
+mod config;
+"#;
+
+        assert!(
+            template
+                .format(&config, Path::new("lib.rs"), content)
+                .ends_with("\n\n// Parses the configuration file.\nmod config;\n"),
+            "A foreign header should be replaced up to the comment below it"
+        );
+    }
+
+    #[test]
+    fn test_check_issue_kinds() {
+        let template = LicenseTemplate::new(
+            "test",
+            CommentStyle::HASH.with_shebang().with_coding_cookie(),
+        );
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let cases = [
+            ("print(1)\n", IssueKind::MissingHeader, 1),
+            (
+                "#!/usr/bin/env python3\n# SPDX-FileCopyrightText: 1997 Someone Else\n# SPDX-License-Identifier: Apache-2.0\n",
+                IssueKind::WrongAuthor,
+                2,
+            ),
+            (
+                "# SPDX-FileCopyrightText: 97 ZhangJian He\n# SPDX-License-Identifier: Apache-2.0\n",
+                IssueKind::WrongYear,
+                1,
+            ),
+            (
+                "# SPDX-FileCopyrightText: 1997 ZhangJian He\n# SPDX-License-Identifier: MIT\n",
+                IssueKind::WrongLicense,
+                1,
+            ),
+            (
+                "# SPDX-FileCopyrightText: 1997 ZhangJian He\n# SPDX-License-Identifier: GPL-2.0\n",
+                IssueKind::WrongLicense,
+                2,
+            ),
+            (
+                "# SPDX-FileCopyrightText: 1997 ZhangJian He\n# License: Apache-2.0\n",
+                IssueKind::MalformedHeader,
+                2,
+            ),
+            (
+                "# SPDX-FileCopyrightText: 1997 ZhangJian He\n# SPDX-License-Identifier: Apache-2.0\n\n# SPDX-FileCopyrightText: 1997 ZhangJian He\n# SPDX-License-Identifier: Apache-2.0\n",
+                IssueKind::DuplicateHeader,
+                4,
+            ),
+        ];
+
+        for (content, kind, line) in cases {
+            let issues = template.check(&config, Path::new("main.py"), content);
+            assert_eq!(issues.len(), 1, "Expected one issue for {:?}", content);
+            assert_eq!(issues[0].kind, kind, "Unexpected kind for {:?}", content);
+            assert_eq!(
+                issues[0].span.start_line, line,
+                "Unexpected line for {:?}",
+                content
+            );
+        }
+
+        let issues = template.check(
+            &config,
+            Path::new("main.py"),
+            "# SPDX-FileCopyrightText: 1997 Someone Else\n# SPDX-License-Identifier: Apache-2.0\n",
+        );
+        assert_eq!(issues[0].expected.as_deref(), Some("ZhangJian He"));
+        assert_eq!(issues[0].found.as_deref(), Some("Someone Else"));
+        assert_eq!(
+            issues[0].message,
+            "copyright holder is not allowed: expected 'ZhangJian He', found 'Someone Else'"
+        );
+    }
+
+    #[test]
+    fn test_check_years() {
+        let template = LicenseTemplate::new("test", CommentStyle::HASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+        config.year_policy.start = Some(1990);
+
+        let header = |years: &str| {
+            format!(
+                "# SPDX-FileCopyrightText: {} ZhangJian He\n# SPDX-License-Identifier: Apache-2.0\n",
+                years
+            )
+        };
+
+        for years in [
+            "1997",
+            "1995-1997",
+            "1995 - 1997",
+            "1995, 1997",
+            "1990-1992, 1997",
+        ] {
+            assert!(
+                template
+                    .check(&config, Path::new("main.py"), &header(years))
+                    .is_empty(),
+                "'{}' should be accepted",
+                years
+            );
+        }
+
+        for (years, message) in [
+            ("1997-1995", "invalid copyright year: expected a year, range or list like '1997', found '1997-1995'"),
+            ("1997,", "invalid copyright year: expected a year, range or list like '1997', found '1997,'"),
+            ("1066", "copyright year 1066 is before the project start year 1990"),
+            ("1995-2999", "copyright year 2999 is in the future"),
+        ] {
+            let issues = template.check(&config, Path::new("main.py"), &header(years));
+            assert_eq!(issues.len(), 1, "'{}' should be rejected", years);
+            assert_eq!(issues[0].kind, IssueKind::WrongYear);
+            assert_eq!(issues[0].message, message);
+        }
+
+        config.year_policy.end = None;
+        assert!(template
+            .check(&config, Path::new("main.py"), &header("2999"))
+            .is_empty());
+
+        // A file that is not on disk counts as modified now.
+        config.year_policy.cover_last_modified = true;
+        let issues = template.check(&config, Path::new("main.py"), &header("1995-1997"));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].expected,
+            Some(format!("1995-{}", Local::now().year())),
+            "The end year should be extended to the last modification"
+        );
+    }
+
+    #[test]
+    fn test_format_covers_last_modified() {
+        let template = LicenseTemplate::new("test", CommentStyle::HASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let header = |years: &str, author: &str| {
+            format!(
+                "# SPDX-FileCopyrightText: {} {}\n# SPDX-License-Identifier: Apache-2.0\n\nprint()\n",
+                years, author
+            )
+        };
+        let now = Local::now().year();
+
+        assert_eq!(
+            template.format(
+                &config,
+                Path::new("main.py"),
+                &header("1997", "ZhangJian He")
+            ),
+            header("1997", "ZhangJian He"),
+            "Years should be kept unless the policy asks to cover the last modification"
+        );
+
+        // A file that is not on disk counts as modified now.
+        config.year_policy.cover_last_modified = true;
+        for (years, author, expected) in [
+            ("1997", "ZhangJian He", format!("1997-{}", now)),
+            (
+                "1990, 1995-1997",
+                "ZhangJian He",
+                format!("1990, 1995-{}", now),
+            ),
+            ("1997", "Someone Else", format!("1997-{}", now)),
+        ] {
+            let formatted = template.format(&config, Path::new("main.py"), &header(years, author));
+            assert_eq!(formatted, header(&expected, "ZhangJian He"));
+            assert!(template
+                .check(&config, Path::new("main.py"), &formatted)
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_format_block_comment() {
+        let template = LicenseTemplate::new("test", CommentStyle::SLASH_STAR);
+        let config = test_config(License::Apache20);
+
+        let content = r#"public class Main {
+}
+"#;
+
+        let expected = r#"/*
+ * Copyright 1997 ZhangJian He
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+public class Main {
+}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("Main.java"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "The license should be added as a block comment"
+        );
+
+        let issues = template.check(&config, Path::new("Main.java"), &formatted_content);
+        assert!(
+            issues.is_empty(),
+            "There should be no issues with a valid license"
+        );
+    }
+
+    #[test]
+    fn test_check_mit_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let config = test_config(License::Mit);
+
+        let content = r#"// Copyright (c) 1997 ZhangJian He
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE."#;
+
+        let issues = template.check(&config, Path::new("main.cpp"), content);
+        assert!(
+            issues.is_empty(),
+            "There should be no issues with a valid MIT license"
+        );
+
+        let issues = template.check(
+            &test_config(License::Apache20),
+            Path::new("main.cpp"),
+            content,
+        );
+        assert_eq!(
+            issues.len(),
+            1,
+            "An MIT header should not satisfy the Apache-2.0 license"
+        );
+    }
+
+    #[test]
+    fn test_check_spdx_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"// SPDX-FileCopyrightText: 1997 ZhangJian He
+// SPDX-License-Identifier: Apache-2.0
+
+int main() {
+    return 0;
+}
+"#;
+
+        let issues = template.check(&config, Path::new("main.cpp"), content);
+        assert!(
+            issues.is_empty(),
+            "There should be no issues with a valid SPDX header"
+        );
+
+        let content = r#"// SPDX-FileCopyrightText: 1997 ZhangJian He
+// SPDX-License-Identifier: MIT
+"#;
+
+        let issues = template.check(&config, Path::new("main.cpp"), content);
+        assert_eq!(
+            issues.len(),
+            1,
+            "There should be one issue for a wrong SPDX identifier"
+        );
+    }
+
+    #[test]
+    fn test_format_spdx_no_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::DOUBLE_SLASH);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"int main() {
+    return 0;
+}
+"#;
+
+        let expected = r#"// SPDX-FileCopyrightText: 1997 ZhangJian He
+// SPDX-License-Identifier: Apache-2.0
+
+int main() {
+    return 0;
+}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.cpp"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "The SPDX header should be added to the content"
+        );
+        assert_eq!(
+            template.format(&config, Path::new("main.cpp"), &formatted_content),
+            formatted_content,
+            "Content should remain unchanged if the SPDX header is already present"
+        );
+    }
+
+    #[test]
+    fn test_check_valid_xml_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::XML);
+        let config = test_config(License::Apache20);
+
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!--
+    Copyright 1997 ZhangJian He
+
+    Licensed under the Apache License, Version 2.0 (the "License");
+    you may not use this file except in compliance with the License.
+    You may obtain a copy of the License at
+
+        http://www.apache.org/licenses/LICENSE-2.0
+
+    Unless required by applicable law or agreed to in writing, software
+    distributed under the License is distributed on an "AS IS" BASIS,
+    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+    See the License for the specific language governing permissions and
+    limitations under the License.
+-->"#;
+
+        let issues = template.check(&config, Path::new("main.xml"), content);
+        assert!(
+            issues.is_empty(),
+            "There should be no issues with a valid license"
+        );
+    }
+
+    #[test]
+    fn test_check_invalid_xml_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::XML);
+        let config = test_config(License::Apache20);
+
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!--
+    Copyright 1997 ZhangJian He
+
+    Licensed under the XYZ License.
+-->
+"#;
+
+        let issues = template.check(&config, Path::new("main.xml"), content);
+        assert_eq!(
+            issues.len(),
+            1,
+            "There should be one issue for an invalid license"
+        );
+    }
+
+    #[test]
+    fn test_format_xml_no_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::XML);
+        let config = test_config(License::Apache20);
+
+        let content = r#"int main() {
+    return 0;
+}
+"#;
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!--
+    Copyright 1997 ZhangJian He
+
+    Licensed under the Apache License, Version 2.0 (the "License");
+    you may not use this file except in compliance with the License.
+    You may obtain a copy of the License at
+
+        http://www.apache.org/licenses/LICENSE-2.0
+
+    Unless required by applicable law or agreed to in writing, software
+    distributed under the License is distributed on an "AS IS" BASIS,
+    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+    See the License for the specific language governing permissions and
+    limitations under the License.
+-->
+int main() {
+    return 0;
+}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.xml"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "The license should be added to the content"
+        );
+    }
+
+    #[test]
+    fn test_format_xml_with_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::XML);
+        let config = test_config(License::Apache20);
+
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!--
+    Copyright 1997 ZhangJian He
+
+    Licensed under the Apache License, Version 2.0 (the "License");
+    you may not use this file except in compliance with the License.
+    You may obtain a copy of the License at
+
+        http://www.apache.org/licenses/LICENSE-2.0
+
+    Unless required by applicable law or agreed to in writing, software
+    distributed under the License is distributed on an "AS IS" BASIS,
+    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+    See the License for the specific language governing permissions and
+    limitations under the License.
+-->
+int main() {
+    return 0;
+}
+"#;
+
+        let formatted_content = template.format(&config, Path::new("main.xml"), content);
+        assert_eq!(
+            formatted_content, content,
+            "Content should remain unchanged if the license is already present"
+        );
+    }
+
+    #[test]
+    fn test_format_xml_spdx_no_license() {
+        let template = LicenseTemplate::new("test", CommentStyle::XML);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"<project>
+</project>
+"#;
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!--
+    SPDX-FileCopyrightText: 1997 ZhangJian He
+    SPDX-License-Identifier: Apache-2.0
+-->
+<project>
+</project>
+"#;
+
+        let formatted_content = template.format(&config, Path::new("pom.xml"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "The SPDX header should be added after the XML declaration"
+        );
+
+        let issues = template.check(&config, Path::new("pom.xml"), &formatted_content);
+        assert!(
+            issues.is_empty(),
+            "There should be no issues with a valid SPDX header"
+        );
+    }
+
+    #[test]
+    fn test_format_xml_keeps_declaration() {
+        let template = LicenseTemplate::new("test", CommentStyle::XML);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+
+        let content = r#"<?xml version='1.0' encoding="utf-8" standalone="yes"?>
+<project>
+</project>
+"#;
+
+        let expected = r#"<?xml version='1.0' encoding="utf-8" standalone="yes"?>
+<!--
+    SPDX-FileCopyrightText: 1997 ZhangJian He
+    SPDX-License-Identifier: Apache-2.0
+-->
+<project>
+</project>
+"#;
+
+        let formatted_content = template.format(&config, Path::new("pom.xml"), content);
+        assert_eq!(
+            formatted_content, expected,
+            "Any XML declaration should be kept instead of adding a second one"
+        );
+        assert!(template
+            .check(&config, Path::new("pom.xml"), &formatted_content)
+            .is_empty());
+        assert_eq!(
+            template.format(&config, Path::new("pom.xml"), &formatted_content),
+            formatted_content
+        );
+    }
+
+    #[test]
+    fn test_format_xml_keeps_declaration_after_bom_or_on_several_lines() {
+        let template = LicenseTemplate::new("test", CommentStyle::XML);
+        let mut config = test_config(License::Apache20);
+        config.header_style = HeaderStyle::Spdx;
+        let header = "<!--\n    SPDX-FileCopyrightText: 1997 ZhangJian He\n    SPDX-License-Identifier: Apache-2.0\n-->\n";
+
+        for declaration in [
+            "\u{feff}<?xml version=\"1.0\" encoding=\"utf-8\"?>",
+            "<?xml version=\"1.0\"\n      encoding=\"utf-8\"?>",
+        ] {
+            let content = format!("{}\n<Project>\n</Project>\n", declaration);
+            let formatted_content = template.format(&config, Path::new("app.csproj.xml"), &content);
+            assert_eq!(
+                formatted_content,
+                format!("{}\n{}<Project>\n</Project>\n", declaration, header),
+                "The declaration should be kept instead of adding a second one"
+            );
+            assert!(template
+                .check(&config, Path::new("app.csproj.xml"), &formatted_content)
+                .is_empty());
+        }
+
+        let content = "<?xml version=\"1.0\"\n<Project/>\n";
+        assert!(
+            !template
+                .format(&config, Path::new("app.xml"), content)
+                .contains("encoding=\"UTF-8\""),
+            "No declaration should be added above an unterminated one"
+        );
+    }
+}
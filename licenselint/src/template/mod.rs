@@ -1,26 +1,20 @@
 use crate::config::Config;
+use crate::error::Result;
 use crate::issue::Issue;
+use std::path::Path;
 
-pub mod arkts_apache20;
-pub mod clang_format_apache20;
-pub mod cmake_apache20;
-pub mod cmake_lists_apache20;
-pub mod cpp_apache20;
-pub mod go_apache20;
-pub mod hpp_apache20;
-pub mod in_apache20;
-pub mod ipp_apache20;
-pub mod java_apache20;
-pub mod properties_apache20;
-pub mod python_apache20;
-pub mod rust_apache20;
-pub mod toml_apache20;
-pub mod tpp_apache20;
-pub mod typescript_apache20;
-pub mod xml_apache20;
-pub mod yaml_apache20;
+pub mod comment_style;
+pub mod license_template;
 
-pub trait LintTemplate {
-    fn check(&self, config: &Config, filename: &str, content: &str) -> Vec<Issue>;
-    fn format(&self, config: &Config, filename: &str, content: &str) -> String;
+/// Templates are shared by the threads linting a directory, hence `Send + Sync`.
+pub trait LintTemplate: Send + Sync {
+    /// A short identifier of the language the template is for, e.g. `rust`.
+    fn language(&self) -> &str;
+    /// Builds whatever the template caches for `config` ahead of the first `check` or `format`,
+    /// failing if the template cannot be used with `config`.
+    fn prepare(&self, _config: &Config) -> Result<()> {
+        Ok(())
+    }
+    fn check(&self, config: &Config, path: &Path, content: &str) -> Vec<Issue>;
+    fn format(&self, config: &Config, path: &Path, content: &str) -> String;
 }